@@ -739,6 +739,10 @@ export namespace contract {
       public value: string = "";
       public from_balance: string = "";
       public to_balance: string = "";
+      public from_balance_status: u32;
+      public to_balance_status: u32;
+      public token: string = "";
+      public kind: u32;
 
       // Decodes Transfer from an ArrayBuffer
       static decode(buf: ArrayBuffer): Transfer {
@@ -800,6 +804,22 @@ export namespace contract {
               obj.to_balance = decoder.string();
               break;
             }
+            case 10: {
+              obj.from_balance_status = decoder.uint32();
+              break;
+            }
+            case 11: {
+              obj.to_balance_status = decoder.uint32();
+              break;
+            }
+            case 12: {
+              obj.token = decoder.string();
+              break;
+            }
+            case 13: {
+              obj.kind = decoder.uint32();
+              break;
+            }
 
             default:
               decoder.skipType(tag & 7);
@@ -859,6 +879,19 @@ export namespace contract {
               __proto.Sizer.varint64(this.to_balance.length) +
               this.to_balance.length
             : 0;
+        size +=
+          this.from_balance_status == 0
+            ? 0
+            : 1 + __proto.Sizer.uint32(this.from_balance_status);
+        size +=
+          this.to_balance_status == 0
+            ? 0
+            : 1 + __proto.Sizer.uint32(this.to_balance_status);
+        size +=
+          this.token.length > 0
+            ? 1 + __proto.Sizer.varint64(this.token.length) + this.token.length
+            : 0;
+        size += this.kind == 0 ? 0 : 1 + __proto.Sizer.uint32(this.kind);
 
         return size;
       }
@@ -927,9 +960,40 @@ export namespace contract {
           encoder.uint32(this.to_balance.length);
           encoder.string(this.to_balance);
         }
+        if (this.from_balance_status != 0) {
+          encoder.uint32(0x50);
+          encoder.uint32(this.from_balance_status);
+        }
+        if (this.to_balance_status != 0) {
+          encoder.uint32(0x58);
+          encoder.uint32(this.to_balance_status);
+        }
+        if (this.token.length > 0) {
+          encoder.uint32(0x62);
+          encoder.uint32(this.token.length);
+          encoder.string(this.token);
+        }
+        if (this.kind != 0) {
+          encoder.uint32(0x68);
+          encoder.uint32(this.kind);
+        }
 
         return buf;
       } // encode Transfer
     } // Transfer
+
+    export enum ExtractionStatus {
+      EXTRACTION_STATUS_UNSPECIFIED = 0,
+      EXTRACTION_STATUS_EXACT = 1,
+      EXTRACTION_STATUS_INFERRED = 2,
+      EXTRACTION_STATUS_MISSING = 3,
+    } // ExtractionStatus
+
+    export enum TransferKind {
+      TRANSFER_KIND_UNSPECIFIED = 0,
+      TRANSFER_KIND_TRANSFER = 1,
+      TRANSFER_KIND_MINT = 2,
+      TRANSFER_KIND_BURN = 3,
+    } // TransferKind
   } // v1
 } // contract
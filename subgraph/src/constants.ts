@@ -3,5 +3,3 @@ import { Address } from "@graphprotocol/graph-ts";
 export const ADDRESS_ZERO: Address = Address.fromString(
   "0x0000000000000000000000000000000000000000"
 );
-
-export const ADDRESS_ZERO_STRING: string = "0x0000000000000000000000000000000000000000";
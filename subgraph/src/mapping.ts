@@ -1,7 +1,7 @@
 import { BigDecimal, BigInt, log } from "@graphprotocol/graph-ts";
 import * as assembly from "./assembly";
 import { Transfer as TransferEvent } from "../generated/grt/GRT";
-import { ADDRESS_ZERO, ADDRESS_ZERO_STRING } from "./constants";
+import { ADDRESS_ZERO } from "./constants";
 import { createAndSaveTransfer, getOrCreateAccount } from "./entity";
 
 export function handleTransfers(bytes: Uint8Array): void {
@@ -30,13 +30,24 @@ export function handleTransfers(bytes: Uint8Array): void {
       BigDecimal.fromString(transferData.value)
     );
 
-    if (transferData.from != ADDRESS_ZERO_STRING) {
+    // A missing balance means the substreams module could not recover it from the storage changes,
+    // so the stored balance is left untouched instead of being overwritten. The zero address on the
+    // other side of mints and burns holds no balance.
+    if (
+      transferData.kind !=
+        assembly.contract.v1.TransferKind.TRANSFER_KIND_MINT &&
+      hasBalance(transferData.from_balance_status)
+    ) {
       let fromAccount = getOrCreateAccount(transferData.from);
       fromAccount.grt_balance = BigInt.fromString(transferData.from_balance);
       fromAccount.save();
     }
 
-    if (transferData.to != ADDRESS_ZERO_STRING) {
+    if (
+      transferData.kind !=
+        assembly.contract.v1.TransferKind.TRANSFER_KIND_BURN &&
+      hasBalance(transferData.to_balance_status)
+    ) {
       let toAccount = getOrCreateAccount(transferData.to);
       toAccount.grt_balance = BigInt.fromString(transferData.to_balance);
       toAccount.save();
@@ -44,6 +55,13 @@ export function handleTransfers(bytes: Uint8Array): void {
   }
 }
 
+function hasBalance(status: u32): bool {
+  return (
+    status == assembly.contract.v1.ExtractionStatus.EXTRACTION_STATUS_EXACT ||
+    status == assembly.contract.v1.ExtractionStatus.EXTRACTION_STATUS_INFERRED
+  );
+}
+
 export function handleTransfer(event: TransferEvent): void {
   const to = event.params.to;
   const from = event.params.from;
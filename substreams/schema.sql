@@ -0,0 +1,41 @@
+create table if not exists transfers
+(
+    id               text not null constraint transfers_pk primary key,
+    token            text not null,
+    evt_tx_hash      text not null,
+    evt_index        integer not null,
+    evt_block_time   timestamp not null,
+    evt_block_number bigint not null,
+    "from"           text not null,
+    "to"             text not null,
+    value            numeric not null
+);
+
+create index if not exists transfers_from_idx on transfers (token, "from");
+create index if not exists transfers_to_idx on transfers (token, "to");
+create index if not exists transfers_block_idx on transfers (evt_block_number);
+
+-- Latest storage-derived balance of each account, keyed by `{token}:{account}`.
+create table if not exists accounts
+(
+    id               text not null constraint accounts_pk primary key,
+    token            text not null,
+    account          text not null,
+    balance          numeric not null,
+    evt_block_number bigint not null
+);
+
+-- Balance of an account right after each transfer it took part in.
+create table if not exists balance_history
+(
+    id               text not null constraint balance_history_pk primary key,
+    token            text not null,
+    account          text not null,
+    balance          numeric not null,
+    evt_tx_hash      text not null,
+    evt_index        integer not null,
+    evt_block_time   timestamp not null,
+    evt_block_number bigint not null
+);
+
+create index if not exists balance_history_account_idx on balance_history (token, account, evt_block_number);
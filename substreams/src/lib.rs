@@ -1,53 +1,649 @@
 mod abi;
-mod pb;
+pub mod params;
+pub mod pb;
+pub mod preimage;
+pub mod stores;
 mod utils;
-use hex_literal::hex;
+use params::{SinkParams, Strictness, TokenParams};
 use pb::contract::v1 as contract;
+use pb::contract::v2 as contract_v2;
+use stores::StoreLookup;
 use substreams::Hex;
+use substreams_database_change::pb::database::DatabaseChanges;
+use substreams_database_change::tables::Tables as DatabaseChangeTables;
 use substreams_entity_change::pb::entity::EntityChanges;
 use substreams_entity_change::tables::Tables as EntityChangesTables;
+use substreams_ethereum::block_view::CallView;
 use substreams_ethereum::pb::eth::v2 as eth;
-use substreams_ethereum::Event;
+use substreams_ethereum::{Event, Function};
 
 #[allow(unused_imports)]
 use num_traits::cast::ToPrimitive;
+use std::collections::HashMap;
 use std::str::FromStr;
-use substreams::scalar::BigDecimal;
-use utils::{extract_balances_from_call, map_hashes_to_addresses};
+use substreams::scalar::{BigDecimal, BigInt};
+use substreams::store::{
+    DeltaBigInt, Deltas, StoreAdd, StoreAddBigInt, StoreDelete, StoreGet, StoreGetInt64, StoreNew,
+    StoreSet, StoreSetBigInt, StoreSetIfNotExists, StoreSetIfNotExistsInt64, StoreSetString,
+};
+use utils::{
+    classify_method, classify_transfer, detect_balance_slot, extract_allowance_consumed_from_call,
+    extract_allowance_from_call, extract_balances_from_call, extract_nonce_from_call,
+    extract_total_supply_from_call, map_hashes_to_address_keys, map_hashes_to_addresses,
+    map_hashes_to_allowance_keys, BalanceResult,
+};
 
 substreams_ethereum::init!();
 
-const GRT_TRACKED_CONTRACT: [u8; 20] = hex!("c944e90c64b2c07662a292be6244bdf05cda44a7");
+#[substreams::handlers::map]
+fn map_transfers(
+    params: String,
+    blk: eth::Block,
+    balance_slots: StoreGetInt64,
+) -> Result<contract::Transfers, substreams::errors::Error> {
+    let params = TokenParams::parse(&params)?;
+
+    Ok(contract::Transfers {
+        transfers: extract_transfers(&params, &blk, &balance_slots)
+            .into_iter()
+            .map(transfer_to_v1)
+            .collect(),
+    })
+}
+
+#[substreams::handlers::map]
+fn map_transfers_v2(
+    params: String,
+    blk: eth::Block,
+    balance_slots: StoreGetInt64,
+) -> Result<contract_v2::Transfers, substreams::errors::Error> {
+    let params = TokenParams::parse(&params)?;
+
+    Ok(contract_v2::Transfers {
+        transfers: extract_transfers(&params, &blk, &balance_slots),
+    })
+}
+
+/// A tracked Transfer log, along with the balances its call left for both accounts.
+struct TransferLog<'a> {
+    call: CallView<'a>,
+    log: &'a eth::Log,
+    transfer: abi::grt_contract::events::Transfer,
+    from_balance: BalanceResult,
+    to_balance: BalanceResult,
+}
+
+/// Decodes the tracked Transfer logs of a block and attributes the balance storage changes of
+/// their call to them. `balance_slots` holds the contents of `store_balance_slots`.
+fn transfer_logs<'a>(
+    params: &'a TokenParams,
+    blk: &'a eth::Block,
+    balance_slots: &'a impl StoreLookup<i64>,
+) -> impl Iterator<Item = TransferLog<'a>> + 'a {
+    blk.transactions().flat_map(move |trx| {
+        trx.logs_with_calls()
+            .filter(move |(log, _)| params.is_tracked(&log.address))
+            .filter_map(move |(log, call)| {
+                let transfer = abi::grt_contract::events::Transfer::match_and_decode(log)?;
+
+                let token = format!("0x{}", Hex::encode(&log.address));
+                // A slot set in the params takes precedence over the detected one.
+                let balance_slot = params
+                    .balance_slot
+                    .or_else(|| balance_slots.lookup(&token).map(|slot| slot as u64));
+                if balance_slot.is_none() {
+                    substreams::log::info!(
+                        "No balance slot detected or set for {}, reading balances from any mapping",
+                        token
+                    );
+                }
+                let hash_to_address = map_hashes_to_addresses(&call, balance_slot);
+                let (from_balance, to_balance) =
+                    extract_balances_from_call(&call, log, &transfer, &hash_to_address);
+
+                Some(TransferLog {
+                    call,
+                    log,
+                    transfer,
+                    from_balance,
+                    to_balance,
+                })
+            })
+    })
+}
+
+/// Decodes the tracked Transfer logs of a block along with the balances read from the storage
+/// changes. `balance_slots` holds the contents of `store_balance_slots`.
+pub fn extract_transfers(
+    params: &TokenParams,
+    blk: &eth::Block,
+    balance_slots: &impl StoreLookup<i64>,
+) -> Vec<contract_v2::Transfer> {
+    transfer_logs(params, blk, balance_slots)
+        .map(|transfer_log| {
+            let TransferLog {
+                call,
+                log,
+                transfer,
+                from_balance,
+                to_balance,
+            } = transfer_log;
+            let trx = call.transaction;
+
+            let kind = classify_transfer(&call, &transfer);
+            let minter = mint_caller(&call, kind);
+            let method = classify_method(&call, &transfer);
+            let (spender, allowance_consumed) = if method == contract::TransferMethod::TransferFrom
+            {
+                let hash_to_allowance_key = map_hashes_to_allowance_keys(&call);
+                (
+                    call.call.caller.clone(),
+                    extract_allowance_consumed_from_call(
+                        &call,
+                        log,
+                        &transfer,
+                        &hash_to_allowance_key,
+                    ),
+                )
+            } else {
+                (Vec::new(), None)
+            };
+
+            contract_v2::Transfer {
+                evt_tx_hash: trx.hash.clone(),
+                evt_index: log.block_index,
+                evt_block_time: Some(blk.timestamp().to_owned()),
+                evt_block_number: blk.number,
+                from: transfer.from,
+                to: transfer.to,
+                value: amount_bytes(&transfer.value),
+                from_balance: from_balance.balance().map(amount_bytes),
+                to_balance: to_balance.balance().map(amount_bytes),
+                from_balance_status: extraction_status(&from_balance) as i32,
+                to_balance_status: extraction_status(&to_balance) as i32,
+                token: log.address.clone(),
+                kind: kind as i32,
+                minter,
+                method: method as i32,
+                spender,
+                allowance_consumed: allowance_consumed.as_ref().map(amount_bytes),
+                tx_from: trx.from.clone(),
+                tx_to: trx.to.clone(),
+                caller: call.call.caller.clone(),
+                call_depth: call.call.depth,
+                log_ordinal: log.ordinal,
+                tx_index: trx.index,
+                gas_price: trx
+                    .gas_price
+                    .as_ref()
+                    .map(|gas_price| gas_price.bytes.clone())
+                    .unwrap_or_default(),
+                evt_block_hash: blk.hash.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Unsigned big-endian encoding of an amount, as carried by contract.v2 messages.
+fn amount_bytes(amount: &BigInt) -> Vec<u8> {
+    amount.to_bytes_be().1
+}
+
+pub fn transfer_to_v1(transfer: contract_v2::Transfer) -> contract::Transfer {
+    let amount = |bytes: &[u8]| BigInt::from_unsigned_bytes_be(bytes).to_string();
+    let address = |bytes: &[u8]| format!("0x{}", Hex::encode(bytes));
+    // `minter` and `spender` are left empty rather than set to "0x" when there is none.
+    let optional_address = |bytes: &[u8]| {
+        if bytes.is_empty() {
+            String::new()
+        } else {
+            address(bytes)
+        }
+    };
+
+    contract::Transfer {
+        evt_tx_hash: address(&transfer.evt_tx_hash),
+        evt_index: transfer.evt_index,
+        evt_block_time: transfer.evt_block_time,
+        evt_block_number: transfer.evt_block_number,
+        from: address(&transfer.from),
+        to: address(&transfer.to),
+        value: amount(&transfer.value),
+        from_balance: transfer.from_balance.as_deref().map(amount),
+        to_balance: transfer.to_balance.as_deref().map(amount),
+        from_balance_status: transfer.from_balance_status,
+        to_balance_status: transfer.to_balance_status,
+        token: address(&transfer.token),
+        kind: transfer.kind,
+        minter: optional_address(&transfer.minter),
+        method: transfer.method,
+        spender: optional_address(&transfer.spender),
+        allowance_consumed: transfer.allowance_consumed.as_deref().map(amount),
+        tx_from: address(&transfer.tx_from),
+        tx_to: address(&transfer.tx_to),
+        caller: address(&transfer.caller),
+        call_depth: transfer.call_depth,
+        log_ordinal: transfer.log_ordinal,
+        tx_index: transfer.tx_index,
+        gas_price: amount(&transfer.gas_price),
+        evt_block_hash: address(&transfer.evt_block_hash),
+    }
+}
+
+#[substreams::handlers::map]
+fn map_balance_changes(
+    params: String,
+    blk: eth::Block,
+    balance_slots: StoreGetInt64,
+) -> Result<contract::BalanceChanges, substreams::errors::Error> {
+    let params = TokenParams::parse(&params)?;
+
+    Ok(extract_balance_changes(&params, &blk, &balance_slots))
+}
+
+/// One balance change per account and tracked Transfer log, for the balances written by the log.
+/// `balance_slots` holds the contents of `store_balance_slots`.
+pub fn extract_balance_changes(
+    params: &TokenParams,
+    blk: &eth::Block,
+    balance_slots: &impl StoreLookup<i64>,
+) -> contract::BalanceChanges {
+    let mut balance_changes = contract::BalanceChanges::default();
+
+    for transfer_log in transfer_logs(params, blk, balance_slots) {
+        let TransferLog {
+            call,
+            log,
+            transfer,
+            from_balance,
+            to_balance,
+        } = transfer_log;
+
+        // Both legs of a self-transfer are the same write to the same account.
+        let mut legs = vec![(&transfer.from, from_balance)];
+        if transfer.from != transfer.to {
+            legs.push((&transfer.to, to_balance));
+        }
+
+        for (account, balance) in legs {
+            if let BalanceResult::Changed {
+                old_value,
+                new_value,
+            } = balance
+            {
+                balance_changes
+                    .balance_changes
+                    .push(contract::BalanceChange {
+                        token: format!("0x{}", Hex::encode(&log.address)),
+                        account: format!("0x{}", Hex::encode(account)),
+                        evt_tx_hash: format!("0x{}", Hex::encode(&call.transaction.hash)),
+                        evt_index: log.block_index,
+                        log_ordinal: log.ordinal,
+                        evt_block_time: Some(blk.timestamp().to_owned()),
+                        evt_block_number: blk.number,
+                        delta: (new_value.clone() - old_value.clone()).to_string(),
+                        old_balance: old_value.to_string(),
+                        new_balance: new_value.to_string(),
+                    });
+            }
+        }
+    }
+
+    balance_changes
+}
+
+#[substreams::handlers::map]
+fn map_balance_slots(
+    params: String,
+    blk: eth::Block,
+) -> Result<contract::BalanceSlots, substreams::errors::Error> {
+    let params = TokenParams::parse(&params)?;
+
+    Ok(extract_balance_slots(&params, &blk))
+}
+
+/// Balance slots detected in a block, without the substreams runtime, so that they can also be
+/// computed natively.
+///
+/// The module cannot read `store_balance_slots`, which it feeds, so a token is detected again in
+/// every block it transfers in, and `store_balance_slots` only keeps the first detection. Nothing
+/// is detected when the slot is pinned by the `balance_slot` param, as it takes precedence anyway.
+pub fn extract_balance_slots(params: &TokenParams, blk: &eth::Block) -> contract::BalanceSlots {
+    let mut balance_slots = contract::BalanceSlots::default();
+    if params.balance_slot.is_some() {
+        return balance_slots;
+    }
+
+    for trx in blk.transactions() {
+        for (log, call) in trx.logs_with_calls() {
+            if !params.is_tracked(&log.address) {
+                continue;
+            }
+
+            let transfer = match abi::grt_contract::events::Transfer::match_and_decode(log) {
+                Some(transfer) => transfer,
+                None => continue,
+            };
+
+            let token = format!("0x{}", Hex::encode(&log.address));
+            if balance_slots
+                .balance_slots
+                .iter()
+                .any(|balance_slot| balance_slot.token == token)
+            {
+                continue;
+            }
+
+            let hash_to_address_key = map_hashes_to_address_keys(&call);
+            if let Some(slot) = detect_balance_slot(&call, log, &transfer, &hash_to_address_key) {
+                balance_slots.balance_slots.push(contract::BalanceSlot {
+                    token,
+                    slot,
+                    evt_tx_hash: format!("0x{}", Hex::encode(&call.transaction.hash)),
+                    evt_index: log.block_index,
+                    evt_block_number: blk.number,
+                });
+            }
+        }
+    }
+
+    balance_slots
+}
+
+#[substreams::handlers::store]
+fn store_balance_slots(balance_slots: contract::BalanceSlots, store: StoreSetIfNotExistsInt64) {
+    // The first detection of a token wins, later ones cannot move its balances mapping.
+    for balance_slot in balance_slots.balance_slots {
+        store.set_if_not_exists(
+            balance_slot.evt_index as u64,
+            &balance_slot.token,
+            &(balance_slot.slot as i64),
+        );
+    }
+}
+
+fn mint_caller(call: &CallView, kind: contract::TransferKind) -> Vec<u8> {
+    if kind != contract::TransferKind::Mint
+        || !abi::grt_contract::functions::Mint::match_call(call.call)
+    {
+        return Vec::new();
+    }
+
+    // `mint` is restricted to minters, so its caller is the minter that printed the tokens. The
+    // contract enforces the role, so `store_minters` is not needed to tell who minted.
+    call.call.caller.clone()
+}
+
+fn minter_key(token: &str, account: &str) -> String {
+    format!("{}:{}", token, account)
+}
+
+#[substreams::handlers::map]
+fn map_minter_roles(
+    params: String,
+    blk: eth::Block,
+) -> Result<contract::MinterRoleChanges, substreams::errors::Error> {
+    let params = TokenParams::parse(&params)?;
+
+    Ok(extract_minter_role_changes(&params, &blk))
+}
+
+pub fn extract_minter_role_changes(
+    params: &TokenParams,
+    blk: &eth::Block,
+) -> contract::MinterRoleChanges {
+    let mut minter_role_changes = contract::MinterRoleChanges::default();
+
+    for trx in blk.transactions() {
+        for (log, call) in trx.logs_with_calls() {
+            if !params.is_tracked(&log.address) {
+                continue;
+            }
+
+            let (account, added) = if let Some(event) =
+                abi::grt_contract::events::MinterAdded::match_and_decode(log)
+            {
+                (event.account, true)
+            } else if let Some(event) =
+                abi::grt_contract::events::MinterRemoved::match_and_decode(log)
+            {
+                (event.account, false)
+            } else {
+                continue;
+            };
+
+            minter_role_changes
+                .minter_role_changes
+                .push(contract::MinterRoleChange {
+                    token: format!("0x{}", Hex::encode(&log.address)),
+                    evt_tx_hash: format!("0x{}", Hex::encode(&call.transaction.hash)),
+                    evt_index: log.block_index,
+                    evt_block_time: Some(blk.timestamp().to_owned()),
+                    evt_block_number: blk.number,
+                    account: format!("0x{}", Hex::encode(account)),
+                    added,
+                    caller: format!("0x{}", Hex::encode(&call.call.caller)),
+                });
+        }
+    }
+
+    minter_role_changes
+}
+
+#[substreams::handlers::store]
+fn store_minters(minter_role_changes: contract::MinterRoleChanges, store: StoreSetString) {
+    for change in minter_role_changes.minter_role_changes {
+        let key = minter_key(&change.token, &change.account);
+
+        if change.added {
+            store.set(change.evt_index as u64, &key, &change.evt_tx_hash);
+        } else {
+            store.delete_prefix(change.evt_index as i64, &key);
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_ownership_changes(
+    params: String,
+    blk: eth::Block,
+) -> Result<contract::OwnershipChanges, substreams::errors::Error> {
+    let params = TokenParams::parse(&params)?;
+    let mut ownership_changes = contract::OwnershipChanges::default();
+
+    for trx in blk.transactions() {
+        for (log, call) in trx.logs_with_calls() {
+            if !params.is_tracked(&log.address) {
+                continue;
+            }
+
+            let (phase, from, to) = if let Some(event) =
+                abi::grt_contract::events::NewPendingOwnership::match_and_decode(log)
+            {
+                // `acceptOwnership` resets the pending governor to the zero address.
+                let phase = if event.to.iter().all(|b| *b == 0) {
+                    contract::OwnershipPhase::Cleared
+                } else {
+                    contract::OwnershipPhase::Proposed
+                };
+                (phase, event.from, event.to)
+            } else if let Some(event) =
+                abi::grt_contract::events::NewOwnership::match_and_decode(log)
+            {
+                (contract::OwnershipPhase::Accepted, event.from, event.to)
+            } else {
+                continue;
+            };
+
+            let function = if abi::grt_contract::functions::TransferOwnership::match_call(call.call)
+            {
+                abi::grt_contract::functions::TransferOwnership::NAME
+            } else if abi::grt_contract::functions::AcceptOwnership::match_call(call.call) {
+                abi::grt_contract::functions::AcceptOwnership::NAME
+            } else {
+                ""
+            };
+
+            ownership_changes
+                .ownership_changes
+                .push(contract::OwnershipChange {
+                    token: format!("0x{}", Hex::encode(&log.address)),
+                    evt_tx_hash: format!("0x{}", Hex::encode(&call.transaction.hash)),
+                    evt_index: log.block_index,
+                    evt_block_time: Some(blk.timestamp().to_owned()),
+                    evt_block_number: blk.number,
+                    phase: phase as i32,
+                    from: format!("0x{}", Hex::encode(from)),
+                    to: format!("0x{}", Hex::encode(to)),
+                    caller: format!("0x{}", Hex::encode(&call.call.caller)),
+                    function: function.to_string(),
+                });
+        }
+    }
+
+    Ok(ownership_changes)
+}
+
+#[substreams::handlers::store]
+fn store_governance(ownership_changes: contract::OwnershipChanges, store: StoreSetString) {
+    for change in ownership_changes.ownership_changes {
+        let ordinal = change.evt_index as u64;
+        let governor_key = format!("{}:governor", change.token);
+        let pending_governor_key = format!("{}:pending_governor", change.token);
+
+        match contract::OwnershipPhase::from_i32(change.phase) {
+            Some(contract::OwnershipPhase::Proposed) => {
+                store.set(ordinal, &pending_governor_key, &change.to)
+            }
+            Some(contract::OwnershipPhase::Accepted) => {
+                store.set(ordinal, &governor_key, &change.to)
+            }
+            Some(contract::OwnershipPhase::Cleared) => {
+                store.delete_prefix(ordinal as i64, &pending_governor_key)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_permits(
+    params: String,
+    blk: eth::Block,
+) -> Result<contract::Permits, substreams::errors::Error> {
+    let params = TokenParams::parse(&params)?;
+    let mut permits = contract::Permits::default();
+
+    for trx in blk.transactions() {
+        // Walk every call of the transaction rather than its root call only, so that permits
+        // relayed through routers and other contracts are found too.
+        for call in trx.calls.iter() {
+            if call.state_reverted || !params.is_tracked(&call.address) {
+                continue;
+            }
+
+            if !abi::grt_contract::functions::Permit::match_call(call) {
+                continue;
+            }
+            let permit = match abi::grt_contract::functions::Permit::decode(call) {
+                Ok(permit) => permit,
+                Err(e) => {
+                    substreams::log::info!("Failed to decode permit call: {}", e);
+                    continue;
+                }
+            };
+
+            let call_view = CallView {
+                transaction: trx,
+                call,
+            };
+            let hash_to_address_key = map_hashes_to_address_keys(&call_view);
+            let nonce = extract_nonce_from_call(&call_view, &permit.u_owner, &hash_to_address_key);
+
+            let approval_evt_index = call
+                .logs
+                .iter()
+                .find(|log| {
+                    matches!(
+                        abi::grt_contract::events::Approval::match_and_decode(log),
+                        Some(approval)
+                            if approval.owner == permit.u_owner
+                                && approval.spender == permit.u_spender
+                    )
+                })
+                .map(|log| log.block_index);
+
+            permits.permits.push(contract::Permit {
+                token: format!("0x{}", Hex::encode(&call.address)),
+                evt_tx_hash: format!("0x{}", Hex::encode(&trx.hash)),
+                evt_block_time: Some(blk.timestamp().to_owned()),
+                evt_block_number: blk.number,
+                call_index: call.index,
+                call_depth: call.depth,
+                caller: format!("0x{}", Hex::encode(&call.caller)),
+                owner: format!("0x{}", Hex::encode(&permit.u_owner)),
+                spender: format!("0x{}", Hex::encode(&permit.u_spender)),
+                value: permit.u_value.to_string(),
+                deadline: permit.u_deadline.to_string(),
+                nonce: nonce.map(|nonce| nonce.to_string()),
+                approval_evt_index,
+            });
+        }
+    }
+
+    Ok(permits)
+}
+
+fn extraction_status(balance: &BalanceResult) -> contract::ExtractionStatus {
+    match balance {
+        BalanceResult::Changed { .. } => contract::ExtractionStatus::Exact,
+        BalanceResult::Unchanged {
+            inferred_value: Some(_),
+        } => contract::ExtractionStatus::Inferred,
+        BalanceResult::Unchanged {
+            inferred_value: None,
+        }
+        | BalanceResult::Missing => contract::ExtractionStatus::Missing,
+    }
+}
 
 #[substreams::handlers::map]
-fn map_transfers(blk: eth::Block) -> Result<contract::Transfers, substreams::errors::Error> {
-    let mut transfers = contract::Transfers::default();
+fn map_approvals(
+    params: String,
+    blk: eth::Block,
+) -> Result<contract::Approvals, substreams::errors::Error> {
+    let params = TokenParams::parse(&params)?;
+    let mut approvals = contract::Approvals::default();
 
-    transfers.transfers.append(
+    approvals.approvals.append(
         &mut blk
             .transactions()
             .flat_map(|trx| {
                 trx.logs_with_calls()
-                    .filter(|(log, _)| log.address == GRT_TRACKED_CONTRACT)
+                    .filter(|(log, _)| params.is_tracked(&log.address))
                     .filter_map(|(log, call)| {
-                        if let Some(transfer) =
-                            abi::grt_contract::events::Transfer::match_and_decode(log)
+                        if let Some(approval) =
+                            abi::grt_contract::events::Approval::match_and_decode(log)
                         {
-                            let hash_to_address = map_hashes_to_addresses(&call);
+                            let hash_to_allowance_key = map_hashes_to_allowance_keys(&call);
 
-                            let (from_balance, to_balance) =
-                                extract_balances_from_call(&call, &transfer, &hash_to_address);
+                            let allowance = extract_allowance_from_call(
+                                &call,
+                                log,
+                                &approval,
+                                &hash_to_allowance_key,
+                            );
 
-                            return Some(contract::Transfer {
+                            return Some(contract::Approval {
                                 evt_tx_hash: format!("0x{}", Hex::encode(&call.transaction.hash)),
                                 evt_index: log.block_index,
                                 evt_block_time: Some(blk.timestamp().to_owned()),
                                 evt_block_number: blk.number,
-                                from: format!("0x{}", Hex::encode(transfer.from)),
-                                to: format!("0x{}", Hex::encode(transfer.to)),
-                                value: transfer.value.to_string(),
-                                from_balance: from_balance.to_string(),
-                                to_balance: to_balance.to_string(),
+                                owner: format!("0x{}", Hex::encode(&approval.owner)),
+                                spender: format!("0x{}", Hex::encode(&approval.spender)),
+                                value: approval.value.to_string(),
+                                allowance: allowance.map(|allowance| allowance.to_string()),
+                                token: format!("0x{}", Hex::encode(&log.address)),
                             });
                         }
                         None
@@ -56,30 +652,362 @@ fn map_transfers(blk: eth::Block) -> Result<contract::Transfers, substreams::err
             .collect(),
     );
 
-    Ok(transfers)
+    Ok(approvals)
+}
+
+#[substreams::handlers::map]
+fn map_total_supply(
+    params: String,
+    blk: eth::Block,
+) -> Result<contract::SupplyChanges, substreams::errors::Error> {
+    let params = TokenParams::parse(&params)?;
+    let mut supply_changes = contract::SupplyChanges::default();
+
+    for trx in blk.transactions() {
+        for (log, call) in trx.logs_with_calls() {
+            if !params.is_tracked(&log.address) {
+                continue;
+            }
+
+            let transfer = match abi::grt_contract::events::Transfer::match_and_decode(log) {
+                Some(transfer) => transfer,
+                None => continue,
+            };
+
+            let kind = classify_transfer(&call, &transfer);
+            let account = match kind {
+                contract::TransferKind::Mint => &transfer.to,
+                contract::TransferKind::Burn => &transfer.from,
+                _ => continue,
+            };
+
+            match extract_total_supply_from_call(
+                &call,
+                log,
+                &transfer,
+                kind,
+                params.total_supply_slot,
+            ) {
+                Some((old_supply, new_supply)) => {
+                    supply_changes.supply_changes.push(contract::SupplyChange {
+                        token: format!("0x{}", Hex::encode(&log.address)),
+                        evt_tx_hash: format!("0x{}", Hex::encode(&call.transaction.hash)),
+                        evt_index: log.block_index,
+                        evt_block_time: Some(blk.timestamp().to_owned()),
+                        evt_block_number: blk.number,
+                        old_supply: old_supply.to_string(),
+                        new_supply: new_supply.to_string(),
+                        cause: kind as i32,
+                        account: format!("0x{}", Hex::encode(account)),
+                    });
+                }
+                None => {
+                    substreams::log::info!(
+                        "No total supply change found for {} in transaction 0x{}",
+                        kind.as_str_name(),
+                        Hex::encode(&call.transaction.hash)
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(supply_changes)
+}
+
+#[substreams::handlers::store]
+fn store_total_supply(supply_changes: contract::SupplyChanges, store: StoreSetBigInt) {
+    for supply_change in supply_changes.supply_changes {
+        match BigInt::from_str(&supply_change.new_supply) {
+            Ok(new_supply) => store.set(
+                supply_change.evt_index as u64,
+                &supply_change.token,
+                &new_supply,
+            ),
+            Err(e) => substreams::log::info!("Failed to parse total supply: {}", e),
+        }
+    }
+}
+
+#[substreams::handlers::store]
+fn store_balances(transfers: contract::Transfers, store: StoreAddBigInt) {
+    for transfer in transfers.transfers {
+        let ordinal = transfer.evt_index as u64;
+        let value = match BigInt::from_str(&transfer.value) {
+            Ok(value) => value,
+            Err(e) => {
+                substreams::log::info!("Failed to parse transfer value: {}", e);
+                continue;
+            }
+        };
+
+        // The zero address only stands for the other side of mints and burns, it holds no balance.
+        if transfer.kind != contract::TransferKind::Mint as i32 {
+            store.add(
+                ordinal,
+                balance_key(&transfer.token, &transfer.from),
+                BigInt::zero() - value.clone(),
+            );
+        }
+        if transfer.kind != contract::TransferKind::Burn as i32 {
+            store.add(ordinal, balance_key(&transfer.token, &transfer.to), value);
+        }
+    }
 }
 
-fn graph_grt_out(transfers: &contract::Transfers, tables: &mut EntityChangesTables) {
-    transfers.transfers.iter().for_each(|evt| {
+fn balance_key(token: &str, account: &str) -> String {
+    format!("{}:{}", token, account)
+}
+
+#[substreams::handlers::map]
+fn map_balance_discrepancies(
+    transfers: contract::Transfers,
+    balances: Deltas<DeltaBigInt>,
+) -> Result<contract::BalanceDiscrepancies, substreams::errors::Error> {
+    // Running balance of each account right after each transfer, keyed by (store key, ordinal). A
+    // self-transfer adds twice at the same ordinal, so later deltas overwrite earlier ones.
+    let running_balances: HashMap<(&str, u64), &BigInt> = balances
+        .deltas
+        .iter()
+        .map(|delta| ((delta.key.as_str(), delta.ordinal), &delta.new_value))
+        .collect();
+
+    let mut discrepancies = contract::BalanceDiscrepancies::default();
+
+    for transfer in &transfers.transfers {
+        let legs = [
+            (
+                &transfer.from,
+                &transfer.from_balance,
+                transfer.from_balance_status,
+            ),
+            (
+                &transfer.to,
+                &transfer.to_balance,
+                transfer.to_balance_status,
+            ),
+        ];
+
+        for (account, storage_balance, storage_balance_status) in legs {
+            let storage_balance = match storage_balance
+                .as_ref()
+                .and_then(|balance| BigInt::from_str(balance).ok())
+            {
+                Some(storage_balance) => storage_balance,
+                None => continue,
+            };
+            let key = balance_key(&transfer.token, account);
+            let store_balance =
+                match running_balances.get(&(key.as_str(), transfer.evt_index as u64)) {
+                    Some(store_balance) => *store_balance,
+                    None => continue,
+                };
+
+            if &storage_balance != store_balance {
+                discrepancies
+                    .discrepancies
+                    .push(contract::BalanceDiscrepancy {
+                        evt_tx_hash: transfer.evt_tx_hash.clone(),
+                        evt_index: transfer.evt_index,
+                        evt_block_number: transfer.evt_block_number,
+                        account: account.clone(),
+                        storage_balance: storage_balance.to_string(),
+                        storage_balance_status,
+                        store_balance: store_balance.to_string(),
+                        difference: (storage_balance - store_balance.clone()).to_string(),
+                    });
+            }
+        }
+    }
+
+    Ok(discrepancies)
+}
+
+pub fn graph_grt_out(
+    transfers: &contract::Transfers,
+    tables: &mut EntityChangesTables,
+    params: &SinkParams,
+) -> Result<(), substreams::errors::Error> {
+    // The entities hold a single `grt_balance` per account, so only the transfers of the token set
+    // in the params are written rather than mixing every tracked token into it.
+    let token = format!("0x{}", Hex::encode(&params.token));
+
+    for evt in transfers.transfers.iter().filter(|evt| evt.token == token) {
+        let fields = match TransferFields::parse(evt) {
+            Ok(fields) => fields,
+            Err(e) => {
+                params.strictness.handle(e)?;
+                continue;
+            }
+        };
+
         tables
-            .create_row(
-                "grt_transfer",
-                format!("{}-{}", evt.evt_tx_hash, evt.evt_index),
-            )
+            .create_row("Transfer", format!("{}-{}", evt.evt_tx_hash, evt.evt_index))
             .set("evt_tx_hash", &evt.evt_tx_hash)
-            .set("evt_index", evt.evt_index)
-            .set("evt_block_time", evt.evt_block_time.as_ref().unwrap())
-            .set("evt_block_number", evt.evt_block_number)
+            .set("evt_index", BigInt::from(evt.evt_index))
+            .set("evt_block_time", fields.evt_block_time.seconds.to_string())
+            .set("evt_block_number", BigInt::from(evt.evt_block_number))
             .set("from", &evt.from)
             .set("to", &evt.to)
-            .set("value", BigDecimal::from_str(&evt.value).unwrap());
-    });
+            .set("value", fields.value);
+
+        // Accounts whose balance could not be recovered from the storage changes are left untouched.
+        if let Some(from_balance) = fields.from_balance {
+            tables
+                .update_row("Account", &evt.from)
+                .set("grt_balance", from_balance);
+        }
+        if let Some(to_balance) = fields.to_balance {
+            tables
+                .update_row("Account", &evt.to)
+                .set("grt_balance", to_balance);
+        }
+    }
+
+    Ok(())
+}
+
+/// Fields of a transfer that need converting before being written out by the sink modules.
+struct TransferFields<'a> {
+    evt_block_time: &'a prost_types::Timestamp,
+    value: BigDecimal,
+    from_balance: Option<BigInt>,
+    to_balance: Option<BigInt>,
+}
+
+impl<'a> TransferFields<'a> {
+    /// Converts the fields of a transfer, with its tx and log in the error when one is malformed.
+    fn parse(evt: &'a contract::Transfer) -> Result<Self, substreams::errors::Error> {
+        let context = |message: String| {
+            substreams::errors::Error::msg(format!(
+                "transfer {} log {} in block {}: {}",
+                evt.evt_tx_hash, evt.evt_index, evt.evt_block_number, message
+            ))
+        };
+        let parse_balance = |balance: &Option<String>| {
+            balance
+                .as_ref()
+                .map(|balance| {
+                    BigInt::from_str(balance)
+                        .map_err(|e| context(format!("invalid balance '{}': {}", balance, e)))
+                })
+                .transpose()
+        };
+
+        let evt_block_time = evt
+            .evt_block_time
+            .as_ref()
+            .ok_or_else(|| context("missing evt_block_time".to_string()))?;
+        let value = BigDecimal::from_str(&evt.value)
+            .map_err(|e| context(format!("invalid value '{}': {}", evt.value, e)))?;
+
+        // The zero address on the other side of mints and burns holds no balance.
+        let from_balance = if evt.kind != contract::TransferKind::Mint as i32 {
+            parse_balance(&evt.from_balance)?
+        } else {
+            None
+        };
+        let to_balance = if evt.kind != contract::TransferKind::Burn as i32 {
+            parse_balance(&evt.to_balance)?
+        } else {
+            None
+        };
+
+        Ok(TransferFields {
+            evt_block_time,
+            value,
+            from_balance,
+            to_balance,
+        })
+    }
 }
 
 #[substreams::handlers::map]
-fn graph_out(events: contract::Transfers) -> Result<EntityChanges, substreams::errors::Error> {
+fn graph_out(
+    params: String,
+    events: contract::Transfers,
+) -> Result<EntityChanges, substreams::errors::Error> {
+    let params = SinkParams::parse(&params)?;
+
     // Initialize Database Changes container
     let mut tables = EntityChangesTables::new();
-    graph_grt_out(&events, &mut tables);
+    graph_grt_out(&events, &mut tables, &params)?;
     Ok(tables.to_entity_changes())
 }
+
+fn db_transfers_out(
+    transfers: &contract::Transfers,
+    tables: &mut DatabaseChangeTables,
+    strictness: Strictness,
+) -> Result<(), substreams::errors::Error> {
+    for evt in &transfers.transfers {
+        let fields = match TransferFields::parse(evt) {
+            Ok(fields) => fields,
+            Err(e) => {
+                strictness.handle(e)?;
+                continue;
+            }
+        };
+
+        tables
+            .upsert_row(
+                "transfers",
+                format!("{}-{}-{}", evt.token, evt.evt_tx_hash, evt.evt_index),
+            )
+            .set("token", &evt.token)
+            .set("evt_tx_hash", &evt.evt_tx_hash)
+            .set("evt_index", evt.evt_index)
+            .set("evt_block_time", fields.evt_block_time)
+            .set("evt_block_number", evt.evt_block_number)
+            .set("from", &evt.from)
+            .set("to", &evt.to)
+            .set("value", fields.value);
+
+        let legs = [
+            (&evt.from, fields.from_balance),
+            (&evt.to, fields.to_balance),
+        ];
+        for (account, balance) in legs {
+            let balance = match balance {
+                Some(balance) => balance,
+                None => continue,
+            };
+
+            tables
+                .upsert_row("accounts", balance_key(&evt.token, account))
+                .set("token", &evt.token)
+                .set("account", account)
+                .set("balance", &balance)
+                .set("evt_block_number", evt.evt_block_number);
+            tables
+                .upsert_row(
+                    "balance_history",
+                    format!(
+                        "{}-{}-{}-{}",
+                        evt.token, evt.evt_tx_hash, evt.evt_index, account
+                    ),
+                )
+                .set("token", &evt.token)
+                .set("account", account)
+                .set("balance", balance)
+                .set("evt_tx_hash", &evt.evt_tx_hash)
+                .set("evt_index", evt.evt_index)
+                .set("evt_block_time", fields.evt_block_time)
+                .set("evt_block_number", evt.evt_block_number);
+        }
+    }
+
+    Ok(())
+}
+
+#[substreams::handlers::map]
+fn db_out(
+    params: String,
+    events: contract::Transfers,
+) -> Result<DatabaseChanges, substreams::errors::Error> {
+    let params = SinkParams::parse(&params)?;
+
+    let mut tables = DatabaseChangeTables::new();
+    db_transfers_out(&events, &mut tables, params.strictness)?;
+    Ok(tables.to_database_changes())
+}
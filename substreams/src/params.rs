@@ -0,0 +1,167 @@
+use hex_literal::hex;
+use substreams::errors::Error;
+use substreams::Hex;
+
+const GRT_TRACKED_CONTRACT: [u8; 20] = hex!("c944e90c64b2c07662a292be6244bdf05cda44a7");
+const ADDRESS_LENGTH: usize = 20;
+
+/// Settings shared by the modules decoding token events, parsed from the module params.
+///
+/// The params are `&` separated `key=value` pairs, for example
+/// `contracts=0xc944e90c64b2c07662a292be6244bdf05cda44a7&balance_slot=2`:
+/// - `contracts`: comma separated addresses of the tracked token contracts, GRT on mainnet when omitted.
+///   `*` tracks every contract emitting events with the standard ERC20 signatures.
+/// - `balance_slot`: storage slot index of the `_balances` mapping, any slot is accepted when omitted.
+/// - `total_supply_slot`: storage slot index of `_totalSupply`, detected from the mint or burn amount
+///   when omitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenParams {
+    pub contracts: ContractFilter,
+    pub balance_slot: Option<u64>,
+    pub total_supply_slot: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContractFilter {
+    Any,
+    Allowlist(Vec<Vec<u8>>),
+}
+
+impl TokenParams {
+    pub fn parse(params: &str) -> Result<Self, Error> {
+        let mut contracts = Vec::new();
+        let mut any_contract = false;
+        let mut balance_slot = None;
+        let mut total_supply_slot = None;
+
+        for pair in params
+            .split('&')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (key, value) = pair.split_once('=').ok_or_else(|| {
+                Error::msg(format!("invalid param '{}', expected key=value", pair))
+            })?;
+
+            match key.trim() {
+                "contracts" if value.trim() == "*" => any_contract = true,
+                "contracts" => {
+                    for address in value.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+                        contracts.push(parse_address(address)?);
+                    }
+                }
+                "balance_slot" => balance_slot = Some(parse_slot(key, value)?),
+                "total_supply_slot" => total_supply_slot = Some(parse_slot(key, value)?),
+                unknown => return Err(Error::msg(format!("unknown param '{}'", unknown))),
+            }
+        }
+
+        let contracts = match (any_contract, contracts.is_empty()) {
+            (true, _) => ContractFilter::Any,
+            (false, true) => ContractFilter::Allowlist(vec![GRT_TRACKED_CONTRACT.to_vec()]),
+            (false, false) => ContractFilter::Allowlist(contracts),
+        };
+
+        Ok(TokenParams {
+            contracts,
+            balance_slot,
+            total_supply_slot,
+        })
+    }
+
+    pub fn is_tracked(&self, address: &[u8]) -> bool {
+        match &self.contracts {
+            ContractFilter::Any => true,
+            ContractFilter::Allowlist(contracts) => {
+                contracts.iter().any(|contract| contract == address)
+            }
+        }
+    }
+}
+
+/// Settings of the sink modules, parsed from the module params.
+///
+/// The params use the same `key=value` format as [`TokenParams`]:
+/// - `mode`: `strict` fails the module on the first malformed transfer, `lenient` logs and skips it.
+///   Defaults to `strict`.
+/// - `token`: address of the token whose transfers `graph_out` writes, GRT on mainnet when omitted.
+///   `db_out` writes every tracked token and ignores it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SinkParams {
+    pub strictness: Strictness,
+    pub token: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strictness {
+    Strict,
+    Lenient,
+}
+
+impl SinkParams {
+    pub fn parse(params: &str) -> Result<Self, Error> {
+        let mut strictness = Strictness::Strict;
+        let mut token = GRT_TRACKED_CONTRACT.to_vec();
+
+        for pair in params
+            .split('&')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (key, value) = pair.split_once('=').ok_or_else(|| {
+                Error::msg(format!("invalid param '{}', expected key=value", pair))
+            })?;
+
+            match (key.trim(), value.trim()) {
+                ("mode", "strict") => strictness = Strictness::Strict,
+                ("mode", "lenient") => strictness = Strictness::Lenient,
+                ("mode", unknown) => {
+                    return Err(Error::msg(format!(
+                        "invalid mode '{}', expected strict or lenient",
+                        unknown
+                    )))
+                }
+                ("token", address) => token = parse_address(address)?,
+                (unknown, _) => return Err(Error::msg(format!("unknown param '{}'", unknown))),
+            }
+        }
+
+        Ok(SinkParams { strictness, token })
+    }
+}
+
+impl Strictness {
+    /// Returns the error in strict mode, logs it and lets the caller skip the record otherwise.
+    pub fn handle(&self, error: Error) -> Result<(), Error> {
+        match self {
+            Strictness::Strict => Err(error),
+            Strictness::Lenient => {
+                substreams::log::info!("Skipping record: {}", error);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_slot(key: &str, value: &str) -> Result<u64, Error> {
+    value
+        .trim()
+        .parse::<u64>()
+        .map_err(|e| Error::msg(format!("invalid {} '{}': {}", key, value, e)))
+}
+
+fn parse_address(address: &str) -> Result<Vec<u8>, Error> {
+    let decoded = Hex::decode(address.trim_start_matches("0x"))
+        .map_err(|e| Error::msg(format!("invalid contract address '{}': {}", address, e)))?;
+
+    if decoded.len() != ADDRESS_LENGTH {
+        return Err(Error::msg(format!(
+            "invalid contract address '{}': expected {} bytes, got {}",
+            address,
+            ADDRESS_LENGTH,
+            decoded.len()
+        )));
+    }
+
+    Ok(decoded)
+}
@@ -22,9 +22,427 @@ pub struct Transfer {
     pub to: ::prost::alloc::string::String,
     #[prost(string, tag="7")]
     pub value: ::prost::alloc::string::String,
+    #[prost(string, optional, tag="8")]
+    pub from_balance: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="9")]
+    pub to_balance: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(enumeration="ExtractionStatus", tag="10")]
+    pub from_balance_status: i32,
+    #[prost(enumeration="ExtractionStatus", tag="11")]
+    pub to_balance_status: i32,
+    #[prost(string, tag="12")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(enumeration="TransferKind", tag="13")]
+    pub kind: i32,
+    /// Caller of the `mint` function for TRANSFER_KIND_MINT transfers, empty otherwise.
+    #[prost(string, tag="14")]
+    pub minter: ::prost::alloc::string::String,
+    #[prost(enumeration="TransferMethod", tag="15")]
+    pub method: i32,
+    /// Caller of `transferFrom`, moving the tokens on behalf of `from`. Empty for other methods.
+    #[prost(string, tag="16")]
+    pub spender: ::prost::alloc::string::String,
+    /// Allowance of the spender used up by `transferFrom`, read from the allowance storage change.
+    #[prost(string, optional, tag="17")]
+    pub allowance_consumed: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, tag="18")]
+    pub tx_from: ::prost::alloc::string::String,
+    #[prost(string, tag="19")]
+    pub tx_to: ::prost::alloc::string::String,
+    /// Immediate caller of the call that emitted the log.
+    #[prost(string, tag="20")]
+    pub caller: ::prost::alloc::string::String,
+    #[prost(uint32, tag="21")]
+    pub call_depth: u32,
+    #[prost(uint64, tag="22")]
+    pub log_ordinal: u64,
+    #[prost(uint32, tag="23")]
+    pub tx_index: u32,
+    #[prost(string, tag="24")]
+    pub gas_price: ::prost::alloc::string::String,
+    #[prost(string, tag="25")]
+    pub evt_block_hash: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Approvals {
+    #[prost(message, repeated, tag="1")]
+    pub approvals: ::prost::alloc::vec::Vec<Approval>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Approval {
+    #[prost(string, tag="1")]
+    pub evt_tx_hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag="2")]
+    pub evt_index: u32,
+    #[prost(message, optional, tag="3")]
+    pub evt_block_time: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(uint64, tag="4")]
+    pub evt_block_number: u64,
+    #[prost(string, tag="5")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="6")]
+    pub spender: ::prost::alloc::string::String,
+    #[prost(string, tag="7")]
+    pub value: ::prost::alloc::string::String,
+    /// Allowance of the spender after the approval, read from the allowance storage change. Unset
+    /// when no storage change could be attributed to the approval.
+    #[prost(string, optional, tag="8")]
+    pub allowance: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, tag="9")]
+    pub token: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BalanceDiscrepancies {
+    #[prost(message, repeated, tag="1")]
+    pub discrepancies: ::prost::alloc::vec::Vec<BalanceDiscrepancy>,
+}
+/// A transfer leg whose storage-derived balance disagrees with the running balance accumulated from
+/// Transfer events in the `store_balances` module.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BalanceDiscrepancy {
+    #[prost(string, tag="1")]
+    pub evt_tx_hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag="2")]
+    pub evt_index: u32,
+    #[prost(uint64, tag="3")]
+    pub evt_block_number: u64,
+    #[prost(string, tag="4")]
+    pub account: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub storage_balance: ::prost::alloc::string::String,
+    #[prost(enumeration="ExtractionStatus", tag="6")]
+    pub storage_balance_status: i32,
+    #[prost(string, tag="7")]
+    pub store_balance: ::prost::alloc::string::String,
     #[prost(string, tag="8")]
-    pub from_balance: ::prost::alloc::string::String,
+    pub difference: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BalanceSlots {
+    #[prost(message, repeated, tag="1")]
+    pub balance_slots: ::prost::alloc::vec::Vec<BalanceSlot>,
+}
+/// Storage slot index of the `_balances` mapping of a token, detected from a transfer between two
+/// distinct accounts.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BalanceSlot {
+    #[prost(string, tag="1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub slot: u64,
+    #[prost(string, tag="3")]
+    pub evt_tx_hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag="4")]
+    pub evt_index: u32,
+    #[prost(uint64, tag="5")]
+    pub evt_block_number: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SupplyChanges {
+    #[prost(message, repeated, tag="1")]
+    pub supply_changes: ::prost::alloc::vec::Vec<SupplyChange>,
+}
+/// Change of the `_totalSupply` storage slot of a token caused by a mint or a burn.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SupplyChange {
+    #[prost(string, tag="1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub evt_tx_hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag="3")]
+    pub evt_index: u32,
+    #[prost(message, optional, tag="4")]
+    pub evt_block_time: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(uint64, tag="5")]
+    pub evt_block_number: u64,
+    #[prost(string, tag="6")]
+    pub old_supply: ::prost::alloc::string::String,
+    #[prost(string, tag="7")]
+    pub new_supply: ::prost::alloc::string::String,
+    #[prost(enumeration="TransferKind", tag="8")]
+    pub cause: i32,
+    /// Recipient of a mint or holder of the burnt tokens.
     #[prost(string, tag="9")]
-    pub to_balance: ::prost::alloc::string::String,
+    pub account: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MinterRoleChanges {
+    #[prost(message, repeated, tag="1")]
+    pub minter_role_changes: ::prost::alloc::vec::Vec<MinterRoleChange>,
+}
+/// Grant or revocation of the minter role, decoded from `MinterAdded` and `MinterRemoved` events.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MinterRoleChange {
+    #[prost(string, tag="1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub evt_tx_hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag="3")]
+    pub evt_index: u32,
+    #[prost(message, optional, tag="4")]
+    pub evt_block_time: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(uint64, tag="5")]
+    pub evt_block_number: u64,
+    #[prost(string, tag="6")]
+    pub account: ::prost::alloc::string::String,
+    #[prost(bool, tag="7")]
+    pub added: bool,
+    /// Caller of the function that emitted the event.
+    #[prost(string, tag="8")]
+    pub caller: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OwnershipChanges {
+    #[prost(message, repeated, tag="1")]
+    pub ownership_changes: ::prost::alloc::vec::Vec<OwnershipChange>,
+}
+/// Step of a governor handover, decoded from `NewPendingOwnership` and `NewOwnership` events.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OwnershipChange {
+    #[prost(string, tag="1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub evt_tx_hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag="3")]
+    pub evt_index: u32,
+    #[prost(message, optional, tag="4")]
+    pub evt_block_time: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(uint64, tag="5")]
+    pub evt_block_number: u64,
+    #[prost(enumeration="OwnershipPhase", tag="6")]
+    pub phase: i32,
+    #[prost(string, tag="7")]
+    pub from: ::prost::alloc::string::String,
+    #[prost(string, tag="8")]
+    pub to: ::prost::alloc::string::String,
+    /// Caller of the function that emitted the event.
+    #[prost(string, tag="9")]
+    pub caller: ::prost::alloc::string::String,
+    /// Name of the token function that emitted the event, empty when it was not called directly.
+    #[prost(string, tag="10")]
+    pub function: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Permits {
+    #[prost(message, repeated, tag="1")]
+    pub permits: ::prost::alloc::vec::Vec<Permit>,
+}
+/// Successful EIP-2612 `permit` call, either sent to the token directly or relayed by another contract.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Permit {
+    #[prost(string, tag="1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub evt_tx_hash: ::prost::alloc::string::String,
+    #[prost(message, optional, tag="3")]
+    pub evt_block_time: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(uint64, tag="4")]
+    pub evt_block_number: u64,
+    #[prost(uint32, tag="5")]
+    pub call_index: u32,
+    #[prost(uint32, tag="6")]
+    pub call_depth: u32,
+    /// Account that called `permit`, the relayer when it differs from the transaction sender.
+    #[prost(string, tag="7")]
+    pub caller: ::prost::alloc::string::String,
+    #[prost(string, tag="8")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="9")]
+    pub spender: ::prost::alloc::string::String,
+    #[prost(string, tag="10")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(string, tag="11")]
+    pub deadline: ::prost::alloc::string::String,
+    /// Nonce of the owner after the permit, read from the `nonces` storage change.
+    #[prost(string, optional, tag="12")]
+    pub nonce: ::core::option::Option<::prost::alloc::string::String>,
+    /// Block index of the Approval log emitted by the permit.
+    #[prost(uint32, optional, tag="13")]
+    pub approval_evt_index: ::core::option::Option<u32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BalanceChanges {
+    #[prost(message, repeated, tag="1")]
+    pub balance_changes: ::prost::alloc::vec::Vec<BalanceChange>,
+}
+/// Write to the balance of an account attributed to a Transfer log.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BalanceChange {
+    #[prost(string, tag="1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub account: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub evt_tx_hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag="4")]
+    pub evt_index: u32,
+    #[prost(uint64, tag="5")]
+    pub log_ordinal: u64,
+    #[prost(message, optional, tag="6")]
+    pub evt_block_time: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(uint64, tag="7")]
+    pub evt_block_number: u64,
+    #[prost(string, tag="8")]
+    pub old_balance: ::prost::alloc::string::String,
+    #[prost(string, tag="9")]
+    pub new_balance: ::prost::alloc::string::String,
+    /// Signed difference `new_balance - old_balance`.
+    #[prost(string, tag="10")]
+    pub delta: ::prost::alloc::string::String,
+}
+/// How a balance was derived from the storage changes of the call that emitted the Transfer log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ExtractionStatus {
+    Unspecified = 0,
+    /// A storage change of the account balance was attributed to the log.
+    Exact = 1,
+    /// The log did not change the balance, which was recovered from another write in the same call.
+    Inferred = 2,
+    /// No balance could be recovered, the balance field is left unset.
+    Missing = 3,
+}
+impl ExtractionStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ExtractionStatus::Unspecified => "EXTRACTION_STATUS_UNSPECIFIED",
+            ExtractionStatus::Exact => "EXTRACTION_STATUS_EXACT",
+            ExtractionStatus::Inferred => "EXTRACTION_STATUS_INFERRED",
+            ExtractionStatus::Missing => "EXTRACTION_STATUS_MISSING",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "EXTRACTION_STATUS_UNSPECIFIED" => Some(Self::Unspecified),
+            "EXTRACTION_STATUS_EXACT" => Some(Self::Exact),
+            "EXTRACTION_STATUS_INFERRED" => Some(Self::Inferred),
+            "EXTRACTION_STATUS_MISSING" => Some(Self::Missing),
+            _ => None,
+        }
+    }
+}
+/// Token function whose call emitted a Transfer log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TransferMethod {
+    Unspecified = 0,
+    Transfer = 1,
+    TransferFrom = 2,
+    /// Any other function, such as `mint`, `burn` or `burnFrom`.
+    Other = 3,
+}
+impl TransferMethod {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            TransferMethod::Unspecified => "TRANSFER_METHOD_UNSPECIFIED",
+            TransferMethod::Transfer => "TRANSFER_METHOD_TRANSFER",
+            TransferMethod::TransferFrom => "TRANSFER_METHOD_TRANSFER_FROM",
+            TransferMethod::Other => "TRANSFER_METHOD_OTHER",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TRANSFER_METHOD_UNSPECIFIED" => Some(Self::Unspecified),
+            "TRANSFER_METHOD_TRANSFER" => Some(Self::Transfer),
+            "TRANSFER_METHOD_TRANSFER_FROM" => Some(Self::TransferFrom),
+            "TRANSFER_METHOD_OTHER" => Some(Self::Other),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum OwnershipPhase {
+    Unspecified = 0,
+    /// A pending governor was proposed by the current governor with `transferOwnership`.
+    Proposed = 1,
+    /// The pending governor became the governor with `acceptOwnership`.
+    Accepted = 2,
+    /// The pending governor was reset, which follows every acceptance.
+    Cleared = 3,
+}
+impl OwnershipPhase {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            OwnershipPhase::Unspecified => "OWNERSHIP_PHASE_UNSPECIFIED",
+            OwnershipPhase::Proposed => "OWNERSHIP_PHASE_PROPOSED",
+            OwnershipPhase::Accepted => "OWNERSHIP_PHASE_ACCEPTED",
+            OwnershipPhase::Cleared => "OWNERSHIP_PHASE_CLEARED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "OWNERSHIP_PHASE_UNSPECIFIED" => Some(Self::Unspecified),
+            "OWNERSHIP_PHASE_PROPOSED" => Some(Self::Proposed),
+            "OWNERSHIP_PHASE_ACCEPTED" => Some(Self::Accepted),
+            "OWNERSHIP_PHASE_CLEARED" => Some(Self::Cleared),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TransferKind {
+    Unspecified = 0,
+    Transfer = 1,
+    /// Tokens created out of the zero address.
+    Mint = 2,
+    /// Tokens sent to the zero address.
+    Burn = 3,
+}
+impl TransferKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            TransferKind::Unspecified => "TRANSFER_KIND_UNSPECIFIED",
+            TransferKind::Transfer => "TRANSFER_KIND_TRANSFER",
+            TransferKind::Mint => "TRANSFER_KIND_MINT",
+            TransferKind::Burn => "TRANSFER_KIND_BURN",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TRANSFER_KIND_UNSPECIFIED" => Some(Self::Unspecified),
+            "TRANSFER_KIND_TRANSFER" => Some(Self::Transfer),
+            "TRANSFER_KIND_MINT" => Some(Self::Mint),
+            "TRANSFER_KIND_BURN" => Some(Self::Burn),
+            _ => None,
+        }
+    }
 }
 // @@protoc_insertion_point(module)
@@ -0,0 +1,68 @@
+// @generated
+/// Binary encoding of contract.v1.Transfers. Addresses and hashes are raw bytes and amounts are
+/// unsigned big-endian integers, so sinks don't have to parse hex or decimal strings back.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Transfers {
+    #[prost(message, repeated, tag="1")]
+    pub transfers: ::prost::alloc::vec::Vec<Transfer>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Transfer {
+    #[prost(bytes="vec", tag="1")]
+    pub evt_tx_hash: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint32, tag="2")]
+    pub evt_index: u32,
+    #[prost(message, optional, tag="3")]
+    pub evt_block_time: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(uint64, tag="4")]
+    pub evt_block_number: u64,
+    #[prost(bytes="vec", tag="5")]
+    pub from: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes="vec", tag="6")]
+    pub to: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes="vec", tag="7")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes="vec", optional, tag="8")]
+    pub from_balance: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes="vec", optional, tag="9")]
+    pub to_balance: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    #[prost(enumeration="super::v1::ExtractionStatus", tag="10")]
+    pub from_balance_status: i32,
+    #[prost(enumeration="super::v1::ExtractionStatus", tag="11")]
+    pub to_balance_status: i32,
+    #[prost(bytes="vec", tag="12")]
+    pub token: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration="super::v1::TransferKind", tag="13")]
+    pub kind: i32,
+    /// Caller of the `mint` function for TRANSFER_KIND_MINT transfers, empty otherwise.
+    #[prost(bytes="vec", tag="14")]
+    pub minter: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration="super::v1::TransferMethod", tag="15")]
+    pub method: i32,
+    /// Caller of `transferFrom`, moving the tokens on behalf of `from`. Empty for other methods.
+    #[prost(bytes="vec", tag="16")]
+    pub spender: ::prost::alloc::vec::Vec<u8>,
+    /// Allowance of the spender used up by `transferFrom`, read from the allowance storage change.
+    #[prost(bytes="vec", optional, tag="17")]
+    pub allowance_consumed: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes="vec", tag="18")]
+    pub tx_from: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes="vec", tag="19")]
+    pub tx_to: ::prost::alloc::vec::Vec<u8>,
+    /// Immediate caller of the call that emitted the log.
+    #[prost(bytes="vec", tag="20")]
+    pub caller: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint32, tag="21")]
+    pub call_depth: u32,
+    #[prost(uint64, tag="22")]
+    pub log_ordinal: u64,
+    #[prost(uint32, tag="23")]
+    pub tx_index: u32,
+    #[prost(bytes="vec", tag="24")]
+    pub gas_price: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes="vec", tag="25")]
+    pub evt_block_hash: ::prost::alloc::vec::Vec<u8>,
+}
+// @@protoc_insertion_point(module)
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::fmt;
+use substreams::Hex;
+
+const WORD_LENGTH: usize = 32;
+const PREIMAGE_LENGTH: usize = 2 * WORD_LENGTH;
+// An address is 20 bytes long, left padded with zeroes to fill the 32 byte word.
+const ADDRESS_PADDING: usize = 12;
+// Storage slot indexes of Solidity state variables are small, so anything that does not fit into
+// the last 8 bytes of the word is not a slot index.
+const SLOT_PADDING: usize = 24;
+
+/// Storage location of a `mapping` entry, `keccak256(key ++ slot)`, recovered from its preimage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingKey {
+    /// Key of the entry, as the 32 byte word it was hashed with.
+    pub key: Vec<u8>,
+    /// Storage slot index of the mapping.
+    pub slot: u64,
+}
+
+impl MappingKey {
+    /// The key of a `mapping(address => ...)` entry.
+    pub fn address(&self) -> Result<Vec<u8>, PreimageError> {
+        decode_address_word(&self.key)
+    }
+}
+
+/// Storage location of a nested `mapping(a => mapping(b => ...))` entry,
+/// `keccak256(key ++ keccak256(outer.key ++ outer.slot))`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NestedMappingKey {
+    /// Entry of the outer mapping holding the inner mapping.
+    pub outer: MappingKey,
+    /// Key of the entry in the inner mapping, as the 32 byte word it was hashed with.
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreimageError {
+    /// The hash or the preimage is not valid hex.
+    InvalidHex(String),
+    /// The preimage is not two 32 byte words. Firehose records the preimages of every keccak256
+    /// computed by the call, not only the ones of mapping keys.
+    InvalidLength(usize),
+    /// The slot word does not fit in a `u64`, so it is not the slot index of a state variable.
+    SlotOutOfRange,
+    /// The key word is not an address left padded with zeroes.
+    NotAnAddress,
+    /// No preimage was recorded for the inner hash of a nested mapping key.
+    UnknownHash(String),
+}
+
+impl fmt::Display for PreimageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreimageError::InvalidHex(e) => write!(f, "invalid hex: {}", e),
+            PreimageError::InvalidLength(length) => write!(
+                f,
+                "preimage is {} bytes long, expected {}",
+                length, PREIMAGE_LENGTH
+            ),
+            PreimageError::SlotOutOfRange => write!(f, "slot word is not a slot index"),
+            PreimageError::NotAnAddress => write!(f, "key word is not an address"),
+            PreimageError::UnknownHash(hash) => write!(f, "no preimage recorded for {}", hash),
+        }
+    }
+}
+
+impl std::error::Error for PreimageError {}
+
+/// Decodes the keccak preimages recorded for a call, keyed by hex hash, into mapping keys.
+pub struct PreimageDecoder<'a> {
+    preimages: &'a HashMap<String, String>,
+}
+
+impl<'a> PreimageDecoder<'a> {
+    pub fn new(preimages: &'a HashMap<String, String>) -> Self {
+        PreimageDecoder { preimages }
+    }
+
+    /// Every recorded preimage decoded as a mapping key, along with the decoded hash. Preimages
+    /// that are not mapping keys come with the reason they were rejected.
+    pub fn mapping_keys(
+        &self,
+    ) -> impl Iterator<Item = (&'a str, Result<(Vec<u8>, MappingKey), PreimageError>)> + 'a {
+        let preimages = self.preimages;
+
+        preimages.iter().map(|(hash, preimage)| {
+            let mapping_key = decode_hash(hash).and_then(|decoded_hash| {
+                decode_mapping_preimage(preimage).map(|key| (decoded_hash, key))
+            });
+            (hash.as_str(), mapping_key)
+        })
+    }
+
+    /// Every recorded preimage decoded as a nested mapping key, along with the decoded hash. The
+    /// preimage of the inner hash has to be recorded as well.
+    pub fn nested_mapping_keys(
+        &self,
+    ) -> impl Iterator<Item = (&'a str, Result<(Vec<u8>, NestedMappingKey), PreimageError>)> + 'a
+    {
+        let preimages = self.preimages;
+
+        preimages.iter().map(move |(hash, preimage)| {
+            let nested_mapping_key = decode_hash(hash).and_then(|decoded_hash| {
+                let (key, inner_hash) = split_words(preimage)?;
+                let inner_hash = Hex::encode(inner_hash);
+                let inner_preimage = preimages
+                    .get(&inner_hash)
+                    .ok_or(PreimageError::UnknownHash(inner_hash))?;
+
+                Ok((
+                    decoded_hash,
+                    NestedMappingKey {
+                        outer: decode_mapping_preimage(inner_preimage)?,
+                        key,
+                    },
+                ))
+            });
+            (hash.as_str(), nested_mapping_key)
+        })
+    }
+}
+
+/// Decodes the hex preimage of a mapping key, the 32 byte key word followed by the 32 byte slot.
+pub fn decode_mapping_preimage(preimage: &str) -> Result<MappingKey, PreimageError> {
+    let (key, slot) = split_words(preimage)?;
+
+    Ok(MappingKey {
+        key,
+        slot: decode_slot_word(&slot)?,
+    })
+}
+
+/// Decodes a 32 byte word holding the slot index of a state variable.
+pub fn decode_slot_word(word: &[u8]) -> Result<u64, PreimageError> {
+    if word.len() != WORD_LENGTH {
+        return Err(PreimageError::InvalidLength(word.len()));
+    }
+    if word[..SLOT_PADDING].iter().any(|b| *b != 0) {
+        return Err(PreimageError::SlotOutOfRange);
+    }
+
+    let mut slot = [0; WORD_LENGTH - SLOT_PADDING];
+    slot.copy_from_slice(&word[SLOT_PADDING..]);
+    Ok(u64::from_be_bytes(slot))
+}
+
+/// Decodes a 32 byte word holding an address.
+pub fn decode_address_word(word: &[u8]) -> Result<Vec<u8>, PreimageError> {
+    if word.len() != WORD_LENGTH || word[..ADDRESS_PADDING].iter().any(|b| *b != 0) {
+        return Err(PreimageError::NotAnAddress);
+    }
+
+    Ok(word[ADDRESS_PADDING..].to_vec())
+}
+
+fn decode_hash(hash: &str) -> Result<Vec<u8>, PreimageError> {
+    Hex::decode(hash).map_err(|e| PreimageError::InvalidHex(e.to_string()))
+}
+
+fn split_words(preimage: &str) -> Result<(Vec<u8>, Vec<u8>), PreimageError> {
+    let mut preimage =
+        Hex::decode(preimage).map_err(|e| PreimageError::InvalidHex(e.to_string()))?;
+    if preimage.len() != PREIMAGE_LENGTH {
+        return Err(PreimageError::InvalidLength(preimage.len()));
+    }
+
+    let second = preimage.split_off(WORD_LENGTH);
+    Ok((preimage, second))
+}
@@ -0,0 +1,20 @@
+use std::collections::HashMap;
+use substreams::store::{StoreGet, StoreGetInt64};
+
+/// Read access to the latest value of a store key. Implemented by the substreams stores inside the
+/// wasm module and by plain maps, so the extraction can also run natively, as in the tests.
+pub trait StoreLookup<T> {
+    fn lookup(&self, key: &str) -> Option<T>;
+}
+
+impl StoreLookup<i64> for StoreGetInt64 {
+    fn lookup(&self, key: &str) -> Option<i64> {
+        self.get_last(key)
+    }
+}
+
+impl<T: Clone> StoreLookup<T> for HashMap<String, T> {
+    fn lookup(&self, key: &str) -> Option<T> {
+        self.get(key).cloned()
+    }
+}
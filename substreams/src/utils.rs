@@ -1,4 +1,3 @@
-use substreams::Hex;
 use substreams_ethereum::block_view::CallView;
 
 #[allow(unused_imports)]
@@ -6,70 +5,465 @@ use num_traits::cast::ToPrimitive;
 use std::collections::HashMap;
 use substreams::scalar::BigInt;
 
-use crate::abi::grt_contract::events::Transfer;
-
-// CONSTANTS for `map_hashes_to_addresses` function
-const EXPECTED_PREIMAGE_LENGTH: usize = 128;
-const ADDRESS_START: usize = 24;
-const ADDRESS_END: usize = 64;
-const PADDING_START: usize = 64;
-const PADDING_END: usize = 126;
-const ZERO_PADDING: &str = "00000000000000000000000000000000000000000000000000000000000000";
-
-pub fn map_hashes_to_addresses(call: &CallView) -> HashMap<Vec<u8>, Vec<u8>> {
-    let mut hash_to_address = HashMap::new();
-
-    for (hash, preimage) in &call.call.keccak_preimages {
-        // The keccak preimage consists of a 32 byte address concantenated with a 32 byte storage slot index.
-        // An ethereum is 20 bytes long so it is padded to 32 bytes with leading zeroes in the preimage.
-        // The storage slot also is padded to 32 bytes with leading zeroes in the preimage.
-        // Check if the preimage is 64 bytes long and that the second 32 bytes consists of leading zeroes for the padding before the storage slot.
-        if preimage.len() != EXPECTED_PREIMAGE_LENGTH
-            || &preimage[PADDING_START..PADDING_END] != ZERO_PADDING
-        {
-            continue;
+use crate::abi::grt_contract::events::{Approval, Transfer};
+use crate::abi::grt_contract::functions;
+use crate::pb::contract::v1::{TransferKind, TransferMethod};
+use crate::preimage::{decode_address_word, decode_slot_word, PreimageDecoder, PreimageError};
+use substreams_ethereum::pb::eth::v2::{Log, StorageChange};
+
+/// Storage location of a `mapping(address => ...)` entry recovered from a keccak preimage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressKey {
+    pub address: Vec<u8>,
+    pub slot: u64,
+}
+
+/// Outcome of attributing the balance storage changes of a call to a single Transfer log.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BalanceResult {
+    /// The balance of the account was written while processing the log, from `old_value` to
+    /// `new_value`.
+    Changed {
+        old_value: BigInt,
+        new_value: BigInt,
+    },
+    /// The log did not move the balance of the account, as for zero-value transfers. The balance is
+    /// inferred from the closest write to the same account elsewhere in the call, when there is one.
+    Unchanged { inferred_value: Option<BigInt> },
+    /// No storage change could be attributed to the log.
+    Missing,
+}
+
+impl BalanceResult {
+    pub fn balance(&self) -> Option<&BigInt> {
+        match self {
+            BalanceResult::Changed { new_value, .. } => Some(new_value),
+            BalanceResult::Unchanged { inferred_value } => inferred_value.as_ref(),
+            BalanceResult::Missing => None,
         }
+    }
+}
 
-        let address_slice = &preimage[ADDRESS_START..ADDRESS_END];
+/// Storage location of an `allowance[owner][spender]` entry recovered from chained keccak preimages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowanceKey {
+    pub owner: Vec<u8>,
+    pub spender: Vec<u8>,
+    pub slot: u64,
+}
 
-        match (Hex::decode(hash), Hex::decode(address_slice)) {
-            (Ok(decoded_hash), Ok(decoded_address)) => {
-                hash_to_address.insert(decoded_hash, decoded_address);
-            }
-            (Err(e), _) | (_, Err(e)) => {
-                substreams::log::info!("Failed to decode hash or address: {}", e);
-                continue;
-            }
+pub fn map_hashes_to_address_keys(call: &CallView) -> HashMap<Vec<u8>, AddressKey> {
+    PreimageDecoder::new(&call.call.keccak_preimages)
+        .mapping_keys()
+        .filter_map(|(hash, mapping_key)| {
+            let address_key = mapping_key.and_then(|(decoded_hash, mapping_key)| {
+                Ok((
+                    decoded_hash,
+                    AddressKey {
+                        address: mapping_key.address()?,
+                        slot: mapping_key.slot,
+                    },
+                ))
+            });
+            keep_decoded(hash, address_key)
+        })
+        .collect()
+}
+
+pub fn map_hashes_to_addresses(
+    call: &CallView,
+    balance_slot: Option<u64>,
+) -> HashMap<Vec<u8>, Vec<u8>> {
+    // When the slot of the balances mapping is known, keys of any other mapping are ignored.
+    map_hashes_to_address_keys(call)
+        .into_iter()
+        .filter(|(_, key)| balance_slot.unwrap_or(key.slot) == key.slot)
+        .map(|(hash, key)| (hash, key.address))
+        .collect()
+}
+
+pub fn detect_balance_slot(
+    call: &CallView,
+    log: &Log,
+    transfer: &Transfer,
+    hash_to_address_key: &HashMap<Vec<u8>, AddressKey>,
+) -> Option<u64> {
+    // Only a transfer between two distinct accounts moves two balances by the same known amount,
+    // mints, burns, self-transfers and zero-value transfers are ambiguous.
+    if transfer.value.is_zero()
+        || transfer.from == transfer.to
+        || is_zero_address(&transfer.from)
+        || is_zero_address(&transfer.to)
+    {
+        return None;
+    }
+
+    let window_start = transfer_window_start(call, log);
+    let mut debited_slots = Vec::new();
+    let mut credited_slots = Vec::new();
+
+    for change in call.call.storage_changes.iter().filter(|change| {
+        change.address == log.address
+            && change.ordinal > window_start
+            && change.ordinal < log.ordinal
+    }) {
+        let key = match hash_to_address_key.get(&change.key) {
+            Some(key) => key,
+            None => continue,
+        };
+        let old_value = BigInt::from_unsigned_bytes_be(&change.old_value);
+        let new_value = BigInt::from_unsigned_bytes_be(&change.new_value);
+
+        if key.address == transfer.from && old_value.clone() - new_value.clone() == transfer.value {
+            debited_slots.push(key.slot);
+        } else if key.address == transfer.to && new_value - old_value == transfer.value {
+            credited_slots.push(key.slot);
         }
     }
 
-    hash_to_address
+    // The balances mapping is the one where the sender is debited and the recipient credited.
+    debited_slots
+        .into_iter()
+        .find(|slot| credited_slots.contains(slot))
 }
 
 pub fn extract_balances_from_call(
     call: &CallView,
+    log: &Log,
     transfer: &Transfer,
     hash_to_address: &HashMap<Vec<u8>, Vec<u8>>,
-) -> (BigInt, BigInt) {
-    let mut from_balance = BigInt::zero();
-    let mut to_balance = BigInt::zero();
-
-    for change in &call.call.storage_changes {
-        let old_value = BigInt::from_signed_bytes_be(&change.old_value);
-        let new_value = BigInt::from_signed_bytes_be(&change.new_value);
-        let diff = new_value.clone() - old_value;
-
-        if let Some(address) = hash_to_address.get(&change.key) {
-            if diff.absolute() == transfer.value {
-                // Determine if this is the 'from' or 'to' address based on the sign of diff
-                if diff < BigInt::zero() && address == &transfer.from {
-                    from_balance = new_value;
-                } else if diff > BigInt::zero() && address == &transfer.to {
-                    to_balance = new_value;
+) -> (BalanceResult, BalanceResult) {
+    let window_start = transfer_window_start(call, log);
+
+    let changes: Vec<&StorageChange> = call
+        .call
+        .storage_changes
+        .iter()
+        .filter(|change| change.address == log.address)
+        .collect();
+
+    (
+        extract_balance(
+            &changes,
+            window_start,
+            log,
+            &transfer.from,
+            transfer,
+            hash_to_address,
+        ),
+        extract_balance(
+            &changes,
+            window_start,
+            log,
+            &transfer.to,
+            transfer,
+            hash_to_address,
+        ),
+    )
+}
+
+pub fn extract_total_supply_from_call(
+    call: &CallView,
+    log: &Log,
+    transfer: &Transfer,
+    kind: TransferKind,
+    total_supply_slot: Option<u64>,
+) -> Option<(BigInt, BigInt)> {
+    let window_start = transfer_window_start(call, log);
+
+    call.call
+        .storage_changes
+        .iter()
+        .filter(|change| {
+            change.address == log.address
+                && change.ordinal > window_start
+                && change.ordinal < log.ordinal
+        })
+        .find(|change| {
+            let old_value = BigInt::from_unsigned_bytes_be(&change.old_value);
+            let new_value = BigInt::from_unsigned_bytes_be(&change.new_value);
+
+            match total_supply_slot {
+                Some(slot) => decode_slot_key(&change.key) == Some(slot),
+                // `_totalSupply` is a plain state variable, so its key is a small slot index rather
+                // than a mapping hash, and it moves by the minted or burnt amount.
+                None => {
+                    decode_slot_key(&change.key).is_some()
+                        && match kind {
+                            TransferKind::Mint => new_value - old_value == transfer.value,
+                            TransferKind::Burn => old_value - new_value == transfer.value,
+                            _ => false,
+                        }
                 }
             }
+        })
+        .map(|change| {
+            (
+                BigInt::from_unsigned_bytes_be(&change.old_value),
+                BigInt::from_unsigned_bytes_be(&change.new_value),
+            )
+        })
+}
+
+pub fn extract_allowance_consumed_from_call(
+    call: &CallView,
+    log: &Log,
+    transfer: &Transfer,
+    hash_to_allowance_key: &HashMap<Vec<u8>, AllowanceKey>,
+) -> Option<BigInt> {
+    // OpenZeppelin 3.x `transferFrom` decreases the allowance after the Transfer log, later versions
+    // before moving the balances, so the allowance write is looked for on both sides of the log,
+    // between the previous and the next Transfer logs of the call.
+    let window_start = transfer_window_start(call, log);
+    let window_end = transfer_window_end(call, log);
+
+    let mut allowance_changes = call.call.storage_changes.iter().filter(|change| {
+        change.address == log.address
+            && change.ordinal > window_start
+            && change.ordinal < window_end
+            && matches!(
+                hash_to_allowance_key.get(&change.key),
+                Some(key) if key.owner == transfer.from && key.spender == call.call.caller
+            )
+    });
+
+    match (allowance_changes.next(), allowance_changes.next_back()) {
+        (Some(first), last) => Some(
+            BigInt::from_unsigned_bytes_be(&first.old_value)
+                - BigInt::from_unsigned_bytes_be(&last.unwrap_or(first).new_value),
+        ),
+        (None, _) => None,
+    }
+}
+
+fn transfer_window_end(call: &CallView, log: &Log) -> u64 {
+    call.call
+        .logs
+        .iter()
+        .filter(|other| {
+            other.address == log.address
+                && other.ordinal > log.ordinal
+                && Transfer::match_log(other)
+        })
+        .map(|other| other.ordinal)
+        .min()
+        .unwrap_or(call.call.end_ordinal)
+}
+
+fn transfer_window_start(call: &CallView, log: &Log) -> u64 {
+    // Balances are written before the Transfer log is emitted, so every storage change between the
+    // previous Transfer log of the same contract in this call and this log was caused by this log.
+    call.call
+        .logs
+        .iter()
+        .filter(|other| {
+            other.address == log.address
+                && other.ordinal < log.ordinal
+                && Transfer::match_log(other)
+        })
+        .map(|other| other.ordinal)
+        .max()
+        .unwrap_or(call.call.begin_ordinal)
+}
+
+fn extract_balance(
+    changes: &[&StorageChange],
+    window_start: u64,
+    log: &Log,
+    address: &[u8],
+    transfer: &Transfer,
+    hash_to_address: &HashMap<Vec<u8>, Vec<u8>>,
+) -> BalanceResult {
+    let account_changes: Vec<&StorageChange> = changes
+        .iter()
+        .filter(|change| hash_to_address.get(&change.key).map(Vec::as_slice) == Some(address))
+        .copied()
+        .collect();
+
+    let mut window_changes = account_changes
+        .iter()
+        .filter(|change| change.ordinal > window_start && change.ordinal < log.ordinal);
+
+    // A self-transfer writes the same key twice, so the balance before the log is taken from the
+    // first write and the balance after it from the last one.
+    match (window_changes.next(), window_changes.next_back()) {
+        (Some(first), last) => BalanceResult::Changed {
+            old_value: BigInt::from_unsigned_bytes_be(&first.old_value),
+            new_value: BigInt::from_unsigned_bytes_be(&last.unwrap_or(first).new_value),
+        },
+        (None, _) if transfer.value.is_zero() || transfer.from == transfer.to => {
+            // Without a write of its own, the balance at the log is the value left by the closest
+            // earlier write, or the value the closest later write started from.
+            let inferred_value = match account_changes
+                .iter()
+                .rev()
+                .find(|change| change.ordinal < log.ordinal)
+            {
+                Some(before) => Some(BigInt::from_unsigned_bytes_be(&before.new_value)),
+                None => account_changes
+                    .iter()
+                    .find(|change| change.ordinal > log.ordinal)
+                    .map(|after| BigInt::from_unsigned_bytes_be(&after.old_value)),
+            };
+
+            BalanceResult::Unchanged { inferred_value }
+        }
+        (None, _) => BalanceResult::Missing,
+    }
+}
+
+pub fn map_hashes_to_allowance_keys(call: &CallView) -> HashMap<Vec<u8>, AllowanceKey> {
+    // An allowance key is keccak(spender ++ keccak(owner ++ slot)), the nested mapping is keyed by
+    // the owner first and the spender second.
+    PreimageDecoder::new(&call.call.keccak_preimages)
+        .nested_mapping_keys()
+        .filter_map(|(hash, nested_mapping_key)| {
+            let allowance_key =
+                nested_mapping_key.and_then(|(decoded_hash, nested_mapping_key)| {
+                    Ok((
+                        decoded_hash,
+                        AllowanceKey {
+                            owner: nested_mapping_key.outer.address()?,
+                            spender: decode_address_word(&nested_mapping_key.key)?,
+                            slot: nested_mapping_key.outer.slot,
+                        },
+                    ))
+                });
+            keep_decoded(hash, allowance_key)
+        })
+        .collect()
+}
+
+/// Keeps the decoded key, logging why a preimage was dropped. Preimages that are not two words
+/// long are not mapping keys at all and are dropped silently.
+fn keep_decoded<T>(hash: &str, decoded: Result<T, PreimageError>) -> Option<T> {
+    match decoded {
+        Ok(decoded) => Some(decoded),
+        Err(PreimageError::InvalidLength(_)) => None,
+        Err(e) => {
+            substreams::log::debug!("Dropped keccak preimage of {}: {}", hash, e);
+            None
+        }
+    }
+}
+
+pub fn extract_allowance_from_call(
+    call: &CallView,
+    log: &Log,
+    approval: &Approval,
+    hash_to_allowance_key: &HashMap<Vec<u8>, AllowanceKey>,
+) -> Option<BigInt> {
+    // The Approval log is emitted right after the allowance is written, so the last write to the key
+    // before the log holds the post-approval allowance.
+    call.call
+        .storage_changes
+        .iter()
+        .filter(|change| change.address == log.address && change.ordinal < log.ordinal)
+        .rev()
+        .find(|change| match hash_to_allowance_key.get(&change.key) {
+            Some(key) => key.owner == approval.owner && key.spender == approval.spender,
+            None => false,
+        })
+        .map(|change| BigInt::from_unsigned_bytes_be(&change.new_value))
+}
+
+pub fn extract_nonce_from_call(
+    call: &CallView,
+    owner: &[u8],
+    hash_to_address_key: &HashMap<Vec<u8>, AddressKey>,
+) -> Option<BigInt> {
+    // `permit` increments `nonces[owner]` by one and leaves the other mappings of the owner alone.
+    call.call
+        .storage_changes
+        .iter()
+        .filter(|change| change.address == call.call.address)
+        .filter(|change| {
+            matches!(hash_to_address_key.get(&change.key), Some(key) if key.address == owner)
+        })
+        .map(|change| {
+            (
+                BigInt::from_unsigned_bytes_be(&change.old_value),
+                BigInt::from_unsigned_bytes_be(&change.new_value),
+            )
+        })
+        .find(|(old_value, new_value)| new_value.clone() - old_value.clone() == BigInt::from(1))
+        .map(|(_, new_value)| new_value)
+}
+
+pub fn classify_transfer(call: &CallView, transfer: &Transfer) -> TransferKind {
+    let kind = match (
+        is_zero_address(&transfer.from),
+        is_zero_address(&transfer.to),
+    ) {
+        (true, false) => TransferKind::Mint,
+        (false, true) => TransferKind::Burn,
+        _ => TransferKind::Transfer,
+    };
+
+    // Cross-check against the token function that emitted the log, a mint or burn call whose
+    // arguments match the log has to agree with the classification from the zero address.
+    let call_kind = if functions::Mint::match_call(call.call) {
+        functions::Mint::decode(call.call)
+            .ok()
+            .filter(|mint| mint.u_to == transfer.to && mint.u_amount == transfer.value)
+            .map(|_| TransferKind::Mint)
+    } else if functions::Burn::match_call(call.call) {
+        functions::Burn::decode(call.call)
+            .ok()
+            .filter(|burn| call.call.caller == transfer.from && burn.amount == transfer.value)
+            .map(|_| TransferKind::Burn)
+    } else if functions::BurnFrom::match_call(call.call) {
+        functions::BurnFrom::decode(call.call)
+            .ok()
+            .filter(|burn| burn.account == transfer.from && burn.amount == transfer.value)
+            .map(|_| TransferKind::Burn)
+    } else {
+        None
+    };
+
+    if let Some(call_kind) = call_kind {
+        if call_kind != kind {
+            substreams::log::info!(
+                "Transfer classified as {} but emitted by a {} call",
+                kind.as_str_name(),
+                call_kind.as_str_name()
+            );
         }
     }
 
-    (from_balance, to_balance)
+    kind
+}
+
+pub fn classify_method(call: &CallView, transfer: &Transfer) -> TransferMethod {
+    // The arguments have to match the log, a single call can emit Transfer logs that do not
+    // correspond to its own arguments, like fee transfers.
+    if functions::Transfer::match_call(call.call) {
+        if let Ok(function) = functions::Transfer::decode(call.call) {
+            if call.call.caller == transfer.from
+                && function.recipient == transfer.to
+                && function.amount == transfer.value
+            {
+                return TransferMethod::Transfer;
+            }
+        }
+    } else if functions::TransferFrom::match_call(call.call) {
+        if let Ok(function) = functions::TransferFrom::decode(call.call) {
+            if function.sender == transfer.from
+                && function.recipient == transfer.to
+                && function.amount == transfer.value
+            {
+                return TransferMethod::TransferFrom;
+            }
+        }
+    }
+
+    TransferMethod::Other
+}
+
+fn is_zero_address(address: &[u8]) -> bool {
+    address.iter().all(|b| *b == 0)
+}
+
+fn decode_slot_key(key: &[u8]) -> Option<u64> {
+    decode_slot_word(key).ok()
 }
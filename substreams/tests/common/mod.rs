@@ -0,0 +1,259 @@
+//! Synthetic Firehose blocks and helpers to run the modules natively in the tests.
+#![allow(dead_code)]
+
+use hex_literal::hex;
+use prost::Message;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use substreams::Hex;
+use substreams_entity_change::pb::entity::{value::Typed, EntityChanges};
+use substreams_entity_change::tables::Tables as EntityChangesTables;
+use substreams_ethereum::pb::eth::v2 as eth;
+use triggers_poc::params::{SinkParams, TokenParams};
+use triggers_poc::pb::contract::v1 as contract;
+
+pub const GRT: [u8; 20] = hex!("c944e90c64b2c07662a292be6244bdf05cda44a7");
+pub const ZERO: [u8; 20] = [0; 20];
+pub const TRANSFER_TOPIC: [u8; 32] =
+    hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+pub const BLOCK_TIMESTAMP: i64 = 1_700_000_000;
+/// Slot of the balances mapping written by the synthetic calls.
+pub const SLOT: u64 = 1;
+
+pub fn address(byte: u8) -> [u8; 20] {
+    [byte; 20]
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", Hex::encode(bytes))
+}
+
+/// 32 byte big-endian word, as stored in the storage changes and the log data.
+pub fn word(value: u64) -> Vec<u8> {
+    let mut word = vec![0; 24];
+    word.extend(value.to_be_bytes());
+    word
+}
+
+pub fn padded(address: &[u8]) -> Vec<u8> {
+    let mut word = vec![0; 12];
+    word.extend(address);
+    word
+}
+
+/// Storage key of `mapping[account]` at `slot`, along with its hex preimage. The modules only look
+/// the key up in `keccak_preimages` and never hash, so a unique stand-in replaces the keccak hash.
+pub fn mapping_key(account: &[u8], slot: u64) -> (Vec<u8>, String) {
+    let mut key = vec![0xaa; 4];
+    key.extend(slot.to_be_bytes());
+    key.extend(account);
+
+    let preimage = format!(
+        "{}{}",
+        Hex::encode(padded(account)),
+        Hex::encode(word(slot))
+    );
+    (key, preimage)
+}
+
+/// Builds a call to a token contract, handing out increasing ordinals in the order its storage
+/// changes and logs are added.
+pub struct CallBuilder {
+    call: eth::Call,
+    ordinal: u64,
+}
+
+impl CallBuilder {
+    pub fn new(caller: [u8; 20], token: [u8; 20]) -> Self {
+        CallBuilder {
+            call: eth::Call {
+                caller: caller.to_vec(),
+                address: token.to_vec(),
+                begin_ordinal: 0,
+                ..Default::default()
+            },
+            ordinal: 1,
+        }
+    }
+
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.call.depth = depth;
+        self
+    }
+
+    pub fn balance_write(mut self, account: [u8; 20], slot: u64, old: u64, new: u64) -> Self {
+        let (key, preimage) = mapping_key(&account, slot);
+        self.call
+            .keccak_preimages
+            .insert(Hex::encode(&key), preimage);
+
+        let ordinal = self.next_ordinal();
+        self.call.storage_changes.push(eth::StorageChange {
+            address: self.call.address.clone(),
+            key,
+            old_value: word(old),
+            new_value: word(new),
+            ordinal,
+        });
+        self
+    }
+
+    pub fn transfer_log(mut self, from: [u8; 20], to: [u8; 20], value: u64) -> Self {
+        let ordinal = self.next_ordinal();
+        let index = self.call.logs.len() as u32;
+        self.call.logs.push(eth::Log {
+            address: self.call.address.clone(),
+            topics: vec![TRANSFER_TOPIC.to_vec(), padded(&from), padded(&to)],
+            data: word(value),
+            index,
+            ordinal,
+            ..Default::default()
+        });
+        self
+    }
+
+    pub fn build(mut self) -> eth::Call {
+        self.call.end_ordinal = self.ordinal;
+        self.call
+    }
+
+    fn next_ordinal(&mut self) -> u64 {
+        let ordinal = self.ordinal;
+        self.ordinal += 1;
+        ordinal
+    }
+}
+
+pub fn transaction(
+    hash_byte: u8,
+    from: [u8; 20],
+    to: [u8; 20],
+    calls: Vec<eth::Call>,
+) -> eth::TransactionTrace {
+    eth::TransactionTrace {
+        hash: vec![hash_byte; 32],
+        from: from.to_vec(),
+        to: to.to_vec(),
+        gas_price: Some(eth::BigInt {
+            bytes: 20_000_000_000u64.to_be_bytes().to_vec(),
+        }),
+        status: eth::TransactionTraceStatus::Succeeded as i32,
+        calls,
+        ..Default::default()
+    }
+}
+
+/// Wraps the transactions in a block, numbering them and their logs as Firehose does.
+pub fn block(number: u64, mut transactions: Vec<eth::TransactionTrace>) -> eth::Block {
+    let mut block_index = 0;
+    for (index, trx) in transactions.iter_mut().enumerate() {
+        trx.index = index as u32;
+        for log in trx.calls.iter_mut().flat_map(|call| call.logs.iter_mut()) {
+            log.block_index = block_index;
+            block_index += 1;
+        }
+    }
+
+    eth::Block {
+        number,
+        hash: vec![0xbb; 32],
+        header: Some(eth::BlockHeader {
+            number,
+            timestamp: Some(prost_types::Timestamp {
+                seconds: BLOCK_TIMESTAMP,
+                nanos: 0,
+            }),
+            ..Default::default()
+        }),
+        transaction_traces: transactions,
+        ..Default::default()
+    }
+}
+
+/// Block 12_000_000 whose single transaction, sent by alice (`address(0x11)`), transfers 40 `token`
+/// to bob (`address(0x22)`), writing alice's balance from 100 to 60 and bob's from 5 to 45.
+pub fn simple_transfer_block(token: [u8; 20]) -> eth::Block {
+    let (alice, bob) = (address(0x11), address(0x22));
+    let call = CallBuilder::new(alice, token)
+        .balance_write(alice, SLOT, 100, 60)
+        .balance_write(bob, SLOT, 5, 45)
+        .transfer_log(alice, bob, 40)
+        .build();
+
+    block(
+        12_000_000,
+        vec![transaction(0x01, alice, token, vec![call])],
+    )
+}
+
+/// Loads a block recorded as a protobuf encoded `sf.ethereum.type.v2.Block` from `tests/fixtures`.
+pub fn load_block(name: &str) -> eth::Block {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    let bytes = std::fs::read(&path)
+        .unwrap_or_else(|e| panic!("failed to read fixture {}: {}", path.display(), e));
+
+    eth::Block::decode(bytes.as_slice())
+        .unwrap_or_else(|e| panic!("failed to decode fixture {}: {}", path.display(), e))
+}
+
+/// Runs `map_transfers` over a block, with the contents of `store_balance_slots` given as a map.
+pub fn map_transfers(
+    params: &str,
+    blk: &eth::Block,
+    balance_slots: &HashMap<String, i64>,
+) -> contract::Transfers {
+    let params = TokenParams::parse(params).expect("valid params");
+
+    contract::Transfers {
+        transfers: triggers_poc::extract_transfers(&params, blk, balance_slots)
+            .into_iter()
+            .map(triggers_poc::transfer_to_v1)
+            .collect(),
+    }
+}
+
+/// Runs `map_balance_changes` over a block, with the contents of `store_balance_slots` given as a
+/// map.
+pub fn map_balance_changes(
+    params: &str,
+    blk: &eth::Block,
+    balance_slots: &HashMap<String, i64>,
+) -> contract::BalanceChanges {
+    let params = TokenParams::parse(params).expect("valid params");
+
+    triggers_poc::extract_balance_changes(&params, blk, balance_slots)
+}
+
+pub fn graph_out(
+    params: &str,
+    transfers: &contract::Transfers,
+) -> Result<EntityChanges, substreams::errors::Error> {
+    let params = SinkParams::parse(params)?;
+
+    let mut tables = EntityChangesTables::new();
+    triggers_poc::graph_grt_out(transfers, &mut tables, &params)?;
+    Ok(tables.to_entity_changes())
+}
+
+/// Value set on a field of an entity, looked up by entity and id since the order of the changes
+/// is not stable.
+pub fn entity_field<'a>(
+    changes: &'a EntityChanges,
+    entity: &str,
+    id: &str,
+    field: &str,
+) -> Option<&'a Typed> {
+    changes
+        .entity_changes
+        .iter()
+        .find(|change| change.entity == entity && change.id == id)?
+        .fields
+        .iter()
+        .find(|f| f.name == field)?
+        .new_value
+        .as_ref()?
+        .typed
+        .as_ref()
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::*;
+use std::collections::HashMap;
+use substreams_entity_change::pb::entity::value::Typed;
+use triggers_poc::pb::contract::v1 as contract;
+
+fn transfers() -> contract::Transfers {
+    map_transfers("", &simple_transfer_block(GRT), &HashMap::new())
+}
+
+#[test]
+fn creates_transfers_and_updates_account_balances() {
+    let changes = graph_out("", &transfers()).expect("graph_out succeeds");
+    let id = format!("{}-0", to_hex(&[0x01; 32]));
+    let (alice, bob) = (to_hex(&address(0x11)), to_hex(&address(0x22)));
+
+    assert_eq!(
+        entity_field(&changes, "Transfer", &id, "value"),
+        Some(&Typed::Bigdecimal("40".to_string()))
+    );
+    assert_eq!(
+        entity_field(&changes, "Transfer", &id, "evt_index"),
+        Some(&Typed::Bigint("0".to_string()))
+    );
+    assert_eq!(
+        entity_field(&changes, "Transfer", &id, "evt_block_time"),
+        Some(&Typed::String(BLOCK_TIMESTAMP.to_string()))
+    );
+    assert_eq!(
+        entity_field(&changes, "Transfer", &id, "from"),
+        Some(&Typed::String(alice.clone()))
+    );
+    assert_eq!(
+        entity_field(&changes, "Account", &alice, "grt_balance"),
+        Some(&Typed::Bigint("60".to_string()))
+    );
+    assert_eq!(
+        entity_field(&changes, "Account", &bob, "grt_balance"),
+        Some(&Typed::Bigint("45".to_string()))
+    );
+}
+
+#[test]
+fn strict_mode_fails_on_malformed_transfers() {
+    let mut transfers = transfers();
+    transfers.transfers[0].value = "not a number".to_string();
+
+    let error = graph_out("mode=strict", &transfers).expect_err("graph_out fails");
+
+    assert!(error.to_string().contains(&to_hex(&[0x01; 32])));
+}
+
+#[test]
+fn lenient_mode_skips_malformed_transfers() {
+    let mut transfers = transfers();
+    transfers.transfers[0].evt_block_time = None;
+
+    let changes = graph_out("mode=lenient", &transfers).expect("graph_out succeeds");
+
+    assert!(changes.entity_changes.is_empty());
+}
+
+#[test]
+fn only_writes_the_transfers_of_the_params_token() {
+    let (bob, other_token) = (address(0x22), address(0x99));
+    let blk = simple_transfer_block(other_token);
+    let transfers = map_transfers("contracts=*", &blk, &HashMap::new());
+    assert_eq!(transfers.transfers.len(), 1);
+
+    let changes = graph_out("", &transfers).expect("graph_out succeeds");
+    assert!(changes.entity_changes.is_empty());
+
+    let params = format!("token={}", to_hex(&other_token));
+    let changes = graph_out(&params, &transfers).expect("graph_out succeeds");
+    assert_eq!(
+        entity_field(&changes, "Account", &to_hex(&bob), "grt_balance"),
+        Some(&Typed::Bigint("45".to_string()))
+    );
+}
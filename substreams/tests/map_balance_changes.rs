@@ -0,0 +1,65 @@
+mod common;
+
+use common::*;
+use std::collections::HashMap;
+use triggers_poc::pb::contract::v1 as contract;
+
+fn run(blk: &substreams_ethereum::pb::eth::v2::Block) -> Vec<contract::BalanceChange> {
+    map_balance_changes("", blk, &HashMap::new()).balance_changes
+}
+
+#[test]
+fn records_old_and_new_balances_of_both_accounts() {
+    let (alice, bob) = (address(0x11), address(0x22));
+
+    let changes = run(&simple_transfer_block(GRT));
+
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].token, to_hex(&GRT));
+    assert_eq!(changes[0].account, to_hex(&alice));
+    assert_eq!(changes[0].evt_tx_hash, to_hex(&[0x01; 32]));
+    assert_eq!(changes[0].evt_block_number, 12_000_000);
+    assert_eq!(changes[0].old_balance, "100");
+    assert_eq!(changes[0].new_balance, "60");
+    assert_eq!(changes[0].delta, "-40");
+    assert_eq!(changes[1].account, to_hex(&bob));
+    assert_eq!(changes[1].old_balance, "5");
+    assert_eq!(changes[1].new_balance, "45");
+    assert_eq!(changes[1].delta, "40");
+}
+
+#[test]
+fn records_a_self_transfer_once_with_a_zero_delta() {
+    let alice = address(0x11);
+    let call = CallBuilder::new(alice, GRT)
+        .balance_write(alice, SLOT, 100, 60)
+        .balance_write(alice, SLOT, 60, 100)
+        .transfer_log(alice, alice, 40)
+        .build();
+    let blk = block(12_000_000, vec![transaction(0x01, alice, GRT, vec![call])]);
+
+    let changes = run(&blk);
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].account, to_hex(&alice));
+    assert_eq!(changes[0].old_balance, "100");
+    assert_eq!(changes[0].new_balance, "100");
+    assert_eq!(changes[0].delta, "0");
+}
+
+#[test]
+fn skips_balances_the_log_did_not_write() {
+    let (alice, bob) = (address(0x11), address(0x22));
+    let call = CallBuilder::new(alice, GRT)
+        .balance_write(alice, SLOT, 100, 60)
+        .transfer_log(alice, bob, 40)
+        .transfer_log(alice, bob, 0)
+        .build();
+    let blk = block(12_000_000, vec![transaction(0x01, alice, GRT, vec![call])]);
+
+    let changes = run(&blk);
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].account, to_hex(&alice));
+    assert_eq!(changes[0].evt_index, 0);
+}
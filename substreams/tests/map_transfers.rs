@@ -0,0 +1,198 @@
+mod common;
+
+use common::*;
+use std::collections::HashMap;
+use triggers_poc::pb::contract::v1 as contract;
+
+fn run(params: &str, blk: &substreams_ethereum::pb::eth::v2::Block) -> contract::Transfers {
+    map_transfers(params, blk, &HashMap::new())
+}
+
+#[test]
+fn reads_balances_from_storage_changes() {
+    let (alice, bob) = (address(0x11), address(0x22));
+    let blk = simple_transfer_block(GRT);
+
+    let transfers = run("", &blk).transfers;
+
+    assert_eq!(transfers.len(), 1);
+    let transfer = &transfers[0];
+    assert_eq!(transfer.evt_tx_hash, to_hex(&[0x01; 32]));
+    assert_eq!(transfer.evt_index, 0);
+    assert_eq!(transfer.evt_block_number, 12_000_000);
+    assert_eq!(transfer.token, to_hex(&GRT));
+    assert_eq!(transfer.from, to_hex(&alice));
+    assert_eq!(transfer.to, to_hex(&bob));
+    assert_eq!(transfer.value, "40");
+    assert_eq!(transfer.from_balance.as_deref(), Some("60"));
+    assert_eq!(transfer.to_balance.as_deref(), Some("45"));
+    assert_eq!(
+        transfer.from_balance_status,
+        contract::ExtractionStatus::Exact as i32
+    );
+    assert_eq!(
+        transfer.to_balance_status,
+        contract::ExtractionStatus::Exact as i32
+    );
+    assert_eq!(transfer.kind, contract::TransferKind::Transfer as i32);
+    assert_eq!(transfer.tx_from, to_hex(&alice));
+    assert_eq!(transfer.caller, to_hex(&alice));
+    assert_eq!(transfer.gas_price, "20000000000");
+}
+
+#[test]
+fn ignores_untracked_contracts() {
+    let blk = simple_transfer_block(address(0x99));
+
+    assert!(run("", &blk).transfers.is_empty());
+    assert_eq!(run("contracts=*", &blk).transfers.len(), 1);
+}
+
+#[test]
+fn reports_missing_balance_writes() {
+    let (alice, bob) = (address(0x11), address(0x22));
+    let call = CallBuilder::new(alice, GRT)
+        .balance_write(alice, SLOT, 100, 60)
+        .transfer_log(alice, bob, 40)
+        .build();
+    let blk = block(12_000_000, vec![transaction(0x01, alice, GRT, vec![call])]);
+
+    let transfer = &run("", &blk).transfers[0];
+
+    assert_eq!(transfer.from_balance.as_deref(), Some("60"));
+    assert_eq!(transfer.to_balance, None);
+    assert_eq!(
+        transfer.to_balance_status,
+        contract::ExtractionStatus::Missing as i32
+    );
+}
+
+#[test]
+fn only_reads_the_configured_balance_slot() {
+    let (alice, bob) = (address(0x11), address(0x22));
+    let call = CallBuilder::new(alice, GRT)
+        .balance_write(alice, SLOT, 100, 60)
+        .balance_write(bob, SLOT, 0, 40)
+        .balance_write(alice, SLOT + 1, 7, 8)
+        .transfer_log(alice, bob, 40)
+        .build();
+    let blk = block(12_000_000, vec![transaction(0x01, alice, GRT, vec![call])]);
+
+    let transfer = &run(&format!("balance_slot={}", SLOT), &blk).transfers[0];
+    assert_eq!(transfer.from_balance.as_deref(), Some("60"));
+
+    let mut balance_slots = HashMap::new();
+    balance_slots.insert(to_hex(&GRT), SLOT as i64);
+    let transfer = &map_transfers("", &blk, &balance_slots).transfers[0];
+    assert_eq!(transfer.from_balance.as_deref(), Some("60"));
+}
+
+#[test]
+fn attributes_writes_to_each_transfer_of_a_call() {
+    let (alice, bob, carol) = (address(0x11), address(0x22), address(0x33));
+    let call = CallBuilder::new(alice, GRT)
+        .balance_write(alice, SLOT, 100, 90)
+        .balance_write(bob, SLOT, 0, 10)
+        .transfer_log(alice, bob, 10)
+        .balance_write(alice, SLOT, 90, 70)
+        .balance_write(carol, SLOT, 0, 20)
+        .transfer_log(alice, carol, 20)
+        .build();
+    let blk = block(12_000_000, vec![transaction(0x01, alice, GRT, vec![call])]);
+
+    let transfers = run("", &blk).transfers;
+
+    assert_eq!(transfers.len(), 2);
+    assert_eq!(transfers[0].from_balance.as_deref(), Some("90"));
+    assert_eq!(transfers[0].to_balance.as_deref(), Some("10"));
+    assert_eq!(transfers[1].evt_index, 1);
+    assert_eq!(transfers[1].from_balance.as_deref(), Some("70"));
+    assert_eq!(transfers[1].to_balance.as_deref(), Some("20"));
+}
+
+#[test]
+fn classifies_mints_from_the_zero_address() {
+    let (minter, bob) = (address(0x44), address(0x22));
+    let call = CallBuilder::new(minter, GRT)
+        .balance_write(bob, SLOT, 0, 500)
+        .transfer_log(ZERO, bob, 500)
+        .build();
+    let blk = block(12_000_000, vec![transaction(0x01, minter, GRT, vec![call])]);
+
+    let transfer = &run("", &blk).transfers[0];
+
+    assert_eq!(transfer.kind, contract::TransferKind::Mint as i32);
+    assert_eq!(transfer.to_balance.as_deref(), Some("500"));
+}
+
+#[test]
+fn attributes_writes_to_same_value_transfers_to_one_account() {
+    let (alice, bob) = (address(0x11), address(0x22));
+    let call = CallBuilder::new(alice, GRT)
+        .balance_write(alice, SLOT, 100, 90)
+        .balance_write(bob, SLOT, 0, 10)
+        .transfer_log(alice, bob, 10)
+        .balance_write(alice, SLOT, 90, 80)
+        .balance_write(bob, SLOT, 10, 20)
+        .transfer_log(alice, bob, 10)
+        .build();
+    let blk = block(12_000_000, vec![transaction(0x01, alice, GRT, vec![call])]);
+
+    let transfers = run("", &blk).transfers;
+
+    assert_eq!(transfers.len(), 2);
+    assert_eq!(transfers[0].from_balance.as_deref(), Some("90"));
+    assert_eq!(transfers[0].to_balance.as_deref(), Some("10"));
+    assert_eq!(transfers[1].from_balance.as_deref(), Some("80"));
+    assert_eq!(transfers[1].to_balance.as_deref(), Some("20"));
+}
+
+#[test]
+fn reads_both_writes_of_a_self_transfer() {
+    let alice = address(0x11);
+    let call = CallBuilder::new(alice, GRT)
+        .balance_write(alice, SLOT, 100, 60)
+        .balance_write(alice, SLOT, 60, 100)
+        .transfer_log(alice, alice, 40)
+        .build();
+    let blk = block(12_000_000, vec![transaction(0x01, alice, GRT, vec![call])]);
+
+    let transfer = &run("", &blk).transfers[0];
+
+    assert_eq!(transfer.from_balance.as_deref(), Some("100"));
+    assert_eq!(transfer.to_balance.as_deref(), Some("100"));
+    assert_eq!(
+        transfer.from_balance_status,
+        contract::ExtractionStatus::Exact as i32
+    );
+    assert_eq!(
+        transfer.to_balance_status,
+        contract::ExtractionStatus::Exact as i32
+    );
+}
+
+#[test]
+fn infers_balances_of_zero_value_transfers() {
+    let (alice, bob, carol) = (address(0x11), address(0x22), address(0x33));
+    let call = CallBuilder::new(alice, GRT)
+        .balance_write(alice, SLOT, 100, 60)
+        .balance_write(bob, SLOT, 0, 40)
+        .transfer_log(alice, bob, 40)
+        .transfer_log(alice, carol, 0)
+        .build();
+    let blk = block(12_000_000, vec![transaction(0x01, alice, GRT, vec![call])]);
+
+    let transfer = &run("", &blk).transfers[1];
+
+    assert_eq!(transfer.value, "0");
+    assert_eq!(transfer.from_balance.as_deref(), Some("60"));
+    assert_eq!(
+        transfer.from_balance_status,
+        contract::ExtractionStatus::Inferred as i32
+    );
+    assert_eq!(transfer.to_balance, None);
+    assert_eq!(
+        transfer.to_balance_status,
+        contract::ExtractionStatus::Missing as i32
+    );
+}
@@ -0,0 +1,143 @@
+use proptest::prelude::*;
+use std::collections::HashMap;
+use substreams::Hex;
+use tiny_keccak::{Hasher, Keccak};
+use triggers_poc::preimage::{
+    decode_mapping_preimage, MappingKey, NestedMappingKey, PreimageDecoder, PreimageError,
+};
+
+fn keccak(bytes: &[u8]) -> Vec<u8> {
+    let mut hasher = Keccak::v256();
+    let mut hash = [0; 32];
+    hasher.update(bytes);
+    hasher.finalize(&mut hash);
+    hash.to_vec()
+}
+
+fn address_word(address: &[u8; 20]) -> Vec<u8> {
+    let mut word = vec![0; 12];
+    word.extend(address);
+    word
+}
+
+fn slot_word(slot: u64) -> Vec<u8> {
+    let mut word = vec![0; 24];
+    word.extend(slot.to_be_bytes());
+    word
+}
+
+/// Hashes `key ++ slot` and records its preimage the way Firehose does, returning the hash.
+fn record(preimages: &mut HashMap<String, String>, key: &[u8], slot: &[u8]) -> Vec<u8> {
+    let preimage = [key, slot].concat();
+    let hash = keccak(&preimage);
+    preimages.insert(Hex::encode(&hash), Hex::encode(&preimage));
+    hash
+}
+
+proptest! {
+    #[test]
+    fn mapping_keys_round_trip(address in any::<[u8; 20]>(), slot in any::<u64>()) {
+        let mut preimages = HashMap::new();
+        let hash = record(&mut preimages, &address_word(&address), &slot_word(slot));
+
+        let mapping_keys: Vec<_> = PreimageDecoder::new(&preimages)
+            .mapping_keys()
+            .map(|(_, mapping_key)| mapping_key)
+            .collect();
+
+        let expected = MappingKey { key: address_word(&address), slot };
+        prop_assert_eq!(mapping_keys, vec![Ok((hash, expected.clone()))]);
+        prop_assert_eq!(expected.address(), Ok(address.to_vec()));
+    }
+
+    #[test]
+    fn nested_mapping_keys_round_trip(
+        owner in any::<[u8; 20]>(),
+        spender in any::<[u8; 20]>(),
+        slot in any::<u64>()
+    ) {
+        let mut preimages = HashMap::new();
+        let inner_hash = record(&mut preimages, &address_word(&owner), &slot_word(slot));
+        let hash = record(&mut preimages, &address_word(&spender), &inner_hash);
+
+        let nested_mapping_keys: HashMap<_, _> = PreimageDecoder::new(&preimages)
+            .nested_mapping_keys()
+            .filter_map(|(_, nested_mapping_key)| nested_mapping_key.ok())
+            .collect();
+
+        let expected = NestedMappingKey {
+            outer: MappingKey { key: address_word(&owner), slot },
+            key: address_word(&spender),
+        };
+        prop_assert_eq!(nested_mapping_keys.len(), 1);
+        prop_assert_eq!(nested_mapping_keys.get(&hash), Some(&expected));
+    }
+
+    #[test]
+    fn rejects_preimages_that_are_not_two_words(
+        preimage in prop::collection::vec(any::<u8>(), 0..160).prop_filter(
+            "mapping key preimages are 64 bytes",
+            |preimage| preimage.len() != 64,
+        )
+    ) {
+        prop_assert_eq!(
+            decode_mapping_preimage(&Hex::encode(&preimage)),
+            Err(PreimageError::InvalidLength(preimage.len()))
+        );
+    }
+
+    #[test]
+    fn rejects_slots_out_of_range(
+        key in any::<[u8; 32]>(),
+        slot in any::<[u8; 32]>().prop_filter(
+            "slot indexes fit in 8 bytes",
+            |slot| slot[..24].iter().any(|b| *b != 0),
+        )
+    ) {
+        let preimage = Hex::encode([key, slot].concat());
+
+        prop_assert_eq!(decode_mapping_preimage(&preimage), Err(PreimageError::SlotOutOfRange));
+    }
+
+    #[test]
+    fn rejects_keys_that_are_not_addresses(
+        key in any::<[u8; 32]>().prop_filter(
+            "addresses are padded with 12 zero bytes",
+            |key| key[..12].iter().any(|b| *b != 0),
+        ),
+        slot in any::<u64>()
+    ) {
+        let preimage = Hex::encode([&key[..], &slot_word(slot)].concat());
+        let mapping_key = decode_mapping_preimage(&preimage);
+
+        prop_assert_eq!(
+            mapping_key.map(|mapping_key| mapping_key.address()),
+            Ok(Err(PreimageError::NotAnAddress))
+        );
+    }
+}
+
+#[test]
+fn rejects_invalid_hex() {
+    assert!(matches!(
+        decode_mapping_preimage("not hex"),
+        Err(PreimageError::InvalidHex(_))
+    ));
+}
+
+#[test]
+fn reports_unknown_inner_hashes() {
+    let mut preimages = HashMap::new();
+    let inner_hash = keccak(b"not recorded");
+    record(&mut preimages, &address_word(&[0x22; 20]), &inner_hash);
+
+    let nested_mapping_keys: Vec<_> = PreimageDecoder::new(&preimages)
+        .nested_mapping_keys()
+        .map(|(_, nested_mapping_key)| nested_mapping_key)
+        .collect();
+
+    assert_eq!(
+        nested_mapping_keys,
+        vec![Err(PreimageError::UnknownHash(Hex::encode(&inner_hash)))]
+    );
+}
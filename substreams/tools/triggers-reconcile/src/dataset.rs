@@ -0,0 +1,287 @@
+use num_bigint::BigInt;
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// Column names accepted for each field, in order of precedence, covering the subgraph `Account`
+// entity exports (`id`, `grt_balance`), the `db_out` tables (whose `id` is `{token}:{account}`)
+// as well as plain exports.
+const TOKEN_COLUMNS: &[&str] = &["token"];
+const ACCOUNT_COLUMNS: &[&str] = &["account", "id", "address"];
+const BALANCE_COLUMNS: &[&str] = &["balance", "grt_balance"];
+const BLOCK_COLUMNS: &[&str] = &["block", "block_number", "evt_block_number"];
+
+/// Balance of an account, as of `block` when the export holds the balance history. `token` is set
+/// when the export covers several tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceRecord {
+    pub token: Option<String>,
+    pub account: String,
+    pub balance: BigInt,
+    pub block: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Jsonl,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(Format::Csv),
+            "jsonl" | "ndjson" | "json" => Some(Format::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "csv" => Ok(Format::Csv),
+            "jsonl" => Ok(Format::Jsonl),
+            unknown => Err(format!(
+                "unknown format '{}', expected csv or jsonl",
+                unknown
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DatasetError {
+    Io(PathBuf, std::io::Error),
+    UnknownFormat(PathBuf),
+    /// A malformed line, numbered from 1.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatasetError::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            DatasetError::UnknownFormat(path) => write!(
+                f,
+                "unknown format of {}, use a .csv or .jsonl file or pass the format",
+                path.display()
+            ),
+            DatasetError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for DatasetError {}
+
+/// Loads a dataset, guessing its format from the file extension when none is given.
+pub fn load(path: &Path, format: Option<Format>) -> Result<Vec<BalanceRecord>, DatasetError> {
+    let format = format
+        .or_else(|| Format::from_path(path))
+        .ok_or_else(|| DatasetError::UnknownFormat(path.to_path_buf()))?;
+    let input =
+        std::fs::read_to_string(path).map_err(|e| DatasetError::Io(path.to_path_buf(), e))?;
+
+    match format {
+        Format::Csv => parse_csv(&input),
+        Format::Jsonl => parse_jsonl(&input),
+    }
+}
+
+/// Keeps the records of `token`, dropping their token so they compare with the records of a single
+/// token dataset. Records without a token are kept as they are.
+pub fn select_token(records: Vec<BalanceRecord>, token: &str) -> Vec<BalanceRecord> {
+    records
+        .into_iter()
+        .filter(|record| record.token.as_deref().unwrap_or(token) == token)
+        .map(|record| BalanceRecord {
+            token: None,
+            ..record
+        })
+        .collect()
+}
+
+/// Parses a CSV export with a header row. Fields may be quoted, but not span several lines.
+pub fn parse_csv(input: &str) -> Result<Vec<BalanceRecord>, DatasetError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let (header_line, header): (usize, Vec<String>) = match lines.next() {
+        Some((line, header)) => (
+            line,
+            split_csv_line(line, header)?
+                .into_iter()
+                .map(|column| column.to_lowercase())
+                .collect(),
+        ),
+        None => return Ok(Vec::new()),
+    };
+    let column = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| header.iter().position(|column| column == name))
+    };
+    let token_column = column(TOKEN_COLUMNS);
+    let account_column =
+        column(ACCOUNT_COLUMNS).ok_or_else(|| missing_column(header_line, ACCOUNT_COLUMNS))?;
+    let balance_column =
+        column(BALANCE_COLUMNS).ok_or_else(|| missing_column(header_line, BALANCE_COLUMNS))?;
+    let block_column = column(BLOCK_COLUMNS);
+
+    lines
+        .map(|(line, row)| {
+            let fields = split_csv_line(line, row)?;
+            let field = |index: usize| {
+                fields
+                    .get(index)
+                    .map(String::as_str)
+                    .ok_or_else(|| DatasetError::Parse {
+                        line,
+                        message: format!("expected {} fields, got {}", header.len(), fields.len()),
+                    })
+            };
+
+            Ok(BalanceRecord {
+                token: match token_column {
+                    Some(token_column) => normalize_token(field(token_column)?),
+                    None => None,
+                },
+                account: normalize_account(field(account_column)?),
+                balance: parse_balance(line, field(balance_column)?)?,
+                block: match block_column {
+                    Some(block_column) => parse_block(line, field(block_column)?)?,
+                    None => None,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Parses a JSON lines export, one object per line.
+pub fn parse_jsonl(input: &str) -> Result<Vec<BalanceRecord>, DatasetError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line, row)| {
+            let object: Value = serde_json::from_str(row).map_err(|e| DatasetError::Parse {
+                line,
+                message: e.to_string(),
+            })?;
+            let field = |names: &[&str]| names.iter().find_map(|name| object.get(name));
+
+            let token = match field(TOKEN_COLUMNS) {
+                Some(Value::String(token)) => normalize_token(token),
+                _ => None,
+            };
+            let account = match field(ACCOUNT_COLUMNS) {
+                Some(Value::String(account)) => normalize_account(account),
+                _ => return Err(missing_column(line, ACCOUNT_COLUMNS)),
+            };
+            let balance = match field(BALANCE_COLUMNS) {
+                Some(Value::String(balance)) => parse_balance(line, balance)?,
+                Some(Value::Number(balance)) => parse_balance(line, &balance.to_string())?,
+                _ => return Err(missing_column(line, BALANCE_COLUMNS)),
+            };
+            let block = match field(BLOCK_COLUMNS) {
+                Some(Value::String(block)) => parse_block(line, block)?,
+                Some(Value::Number(block)) => parse_block(line, &block.to_string())?,
+                _ => None,
+            };
+
+            Ok(BalanceRecord {
+                token,
+                account,
+                balance,
+                block,
+            })
+        })
+        .collect()
+}
+
+/// Splits a CSV line into its fields, unquoting the quoted ones, in which `""` stands for a quote.
+fn split_csv_line(line: usize, row: &str) -> Result<Vec<String>, DatasetError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = row.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.trim().is_empty() => {
+                quoted = true;
+                field.clear();
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(DatasetError::Parse {
+            line,
+            message: "unterminated quoted field".to_string(),
+        });
+    }
+    fields.push(field.trim().to_string());
+    Ok(fields)
+}
+
+fn normalize_token(token: &str) -> Option<String> {
+    let token = token.trim();
+    if token.is_empty() {
+        return None;
+    }
+    Some(token.to_lowercase())
+}
+
+fn normalize_account(account: &str) -> String {
+    account.trim().to_lowercase()
+}
+
+fn parse_balance(line: usize, balance: &str) -> Result<BigInt, DatasetError> {
+    BigInt::from_str(balance.trim()).map_err(|e| DatasetError::Parse {
+        line,
+        message: format!("invalid balance '{}': {}", balance, e),
+    })
+}
+
+fn parse_block(line: usize, block: &str) -> Result<Option<u64>, DatasetError> {
+    if block.trim().is_empty() {
+        return Ok(None);
+    }
+
+    block
+        .trim()
+        .parse::<u64>()
+        .map(Some)
+        .map_err(|e| DatasetError::Parse {
+            line,
+            message: format!("invalid block '{}': {}", block, e),
+        })
+}
+
+fn missing_column(line: usize, names: &[&str]) -> DatasetError {
+    DatasetError::Parse {
+        line,
+        message: format!("missing field, expected one of {}", names.join(", ")),
+    }
+}
@@ -0,0 +1,5 @@
+//! Compares the account balances exported from two sources, such as the normal and the
+//! Substreams trigger powered subgraphs, or `graph_out` runs.
+
+pub mod dataset;
+pub mod reconcile;
@@ -0,0 +1,164 @@
+use num_bigint::BigInt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use triggers_reconcile::dataset::{self, Format};
+use triggers_reconcile::reconcile::{reconcile, Report};
+
+const USAGE: &str = "\
+Usage: triggers-reconcile [OPTIONS] <LEFT> <RIGHT>
+
+Compares two exported account balance datasets and reports the accounts whose balances differ.
+Datasets are CSV files with a header row or JSON lines files, with an account (`account` or `id`),
+a balance (`balance` or `grt_balance`) and optionally a block (`block` or `block_number`) per row.
+Datasets with a `token` column, such as the db_out exports, are compared per token and account.
+
+Options:
+    --format <csv|jsonl>        Format of both datasets, guessed from the extension by default
+    --left-format <csv|jsonl>   Format of the left dataset
+    --right-format <csv|jsonl>  Format of the right dataset
+    --token <ADDRESS>           Only compare the balances of this token, to compare a dataset with
+                                a `token` column to a single token one
+    --limit <N>                 Number of mismatched accounts to print, 50 by default
+    -h, --help                  Print this help
+
+Exits with 0 when the balances match, 1 when they don't and 2 on errors.";
+
+struct Args {
+    left: PathBuf,
+    right: PathBuf,
+    left_format: Option<Format>,
+    right_format: Option<Format>,
+    token: Option<String>,
+    limit: usize,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut paths = Vec::new();
+    let (mut left_format, mut right_format) = (None, None);
+    let mut token = None;
+    let mut limit = 50;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", name))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--format" => {
+                let format = value("--format")?.parse::<Format>()?;
+                left_format = Some(format);
+                right_format = Some(format);
+            }
+            "--left-format" => left_format = Some(value("--left-format")?.parse()?),
+            "--right-format" => right_format = Some(value("--right-format")?.parse()?),
+            "--token" => token = Some(value("--token")?.to_lowercase()),
+            "--limit" => {
+                limit = value("--limit")?
+                    .parse()
+                    .map_err(|e| format!("invalid --limit: {}", e))?
+            }
+            flag if flag.starts_with('-') => return Err(format!("unknown option {}", flag)),
+            path => paths.push(PathBuf::from(path)),
+        }
+    }
+
+    match <[PathBuf; 2]>::try_from(paths) {
+        Ok([left, right]) => Ok(Some(Args {
+            left,
+            right,
+            left_format,
+            right_format,
+            token,
+            limit,
+        })),
+        Err(paths) => Err(format!("expected 2 datasets, got {}", paths.len())),
+    }
+}
+
+fn format_balance(balance: &Option<BigInt>) -> String {
+    balance
+        .as_ref()
+        .map_or_else(|| "missing".to_string(), |balance| balance.to_string())
+}
+
+fn print_report(args: &Args, report: &Report) {
+    println!("left: {}", args.left.display());
+    println!("right: {}", args.right.display());
+
+    if !report.mismatches.is_empty() {
+        println!();
+        println!("token,account,left,right,difference,first_diverging_block");
+        for mismatch in report.mismatches.iter().take(args.limit) {
+            println!(
+                "{},{},{},{},{},{}",
+                mismatch.token.as_deref().unwrap_or_default(),
+                mismatch.account,
+                format_balance(&mismatch.left),
+                format_balance(&mismatch.right),
+                mismatch.difference(),
+                mismatch
+                    .first_diverging_block
+                    .map_or_else(|| "unknown".to_string(), |block| block.to_string())
+            );
+        }
+        if report.mismatches.len() > args.limit {
+            println!("... {} more", report.mismatches.len() - args.limit);
+        }
+    }
+
+    let stats = &report.stats;
+    println!();
+    println!("accounts in left: {}", stats.left_accounts);
+    println!("accounts in right: {}", stats.right_accounts);
+    println!("accounts in both: {}", stats.common_accounts);
+    println!("accounts only in left: {}", stats.only_left);
+    println!("accounts only in right: {}", stats.only_right);
+    println!("matching accounts: {}", stats.matching);
+    println!("mismatched accounts: {}", stats.mismatched);
+    println!(
+        "total absolute difference: {}",
+        stats.total_absolute_difference
+    );
+    println!("max absolute difference: {}", stats.max_absolute_difference);
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let load = |path: &Path, format| {
+        dataset::load(path, format)
+            .map(|records| match &args.token {
+                Some(token) => dataset::select_token(records, token),
+                None => records,
+            })
+            .map_err(|e| eprintln!("error: {}: {}", path.display(), e))
+    };
+    let (left, right) = match (
+        load(&args.left, args.left_format),
+        load(&args.right, args.right_format),
+    ) {
+        (Ok(left), Ok(right)) => (left, right),
+        _ => return ExitCode::from(2),
+    };
+
+    let report = reconcile(&left, &right);
+    print_report(&args, &report);
+
+    if report.mismatches.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
@@ -0,0 +1,171 @@
+use crate::dataset::BalanceRecord;
+use num_bigint::BigInt;
+use num_traits::{Signed, Zero};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Account whose balance history or final balance differs between the two datasets. An account
+/// missing from one dataset is compared as a zero balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountMismatch {
+    /// Token of the balance, when the datasets cover several tokens.
+    pub token: Option<String>,
+    pub account: String,
+    pub left: Option<BigInt>,
+    pub right: Option<BigInt>,
+    /// First block at which the balance histories disagree, when the datasets carry blocks.
+    pub first_diverging_block: Option<u64>,
+}
+
+impl AccountMismatch {
+    /// `left - right`, missing balances counting as zero.
+    pub fn difference(&self) -> BigInt {
+        self.left.clone().unwrap_or_default() - self.right.clone().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub left_accounts: usize,
+    pub right_accounts: usize,
+    pub common_accounts: usize,
+    pub only_left: usize,
+    pub only_right: usize,
+    pub matching: usize,
+    pub mismatched: usize,
+    pub total_absolute_difference: BigInt,
+    pub max_absolute_difference: BigInt,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Mismatched accounts, ordered by first diverging block, token then account.
+    pub mismatches: Vec<AccountMismatch>,
+    pub stats: Stats,
+}
+
+/// Balance history of an account: the balance as of each block, and the balance of the records
+/// without a block, which are taken as the latest.
+#[derive(Debug, Default)]
+struct History {
+    by_block: BTreeMap<u64, BigInt>,
+    unblocked: Option<BigInt>,
+}
+
+impl History {
+    fn latest(&self) -> Option<&BigInt> {
+        self.unblocked
+            .as_ref()
+            .or_else(|| self.by_block.values().next_back())
+    }
+}
+
+/// Balances are tracked per token and account, so that the tokens of multi-token exports are not
+/// merged together.
+type Key<'a> = (Option<&'a str>, &'a str);
+
+fn histories(records: &[BalanceRecord]) -> BTreeMap<Key<'_>, History> {
+    let mut histories: BTreeMap<Key, History> = BTreeMap::new();
+
+    // Later records of the same account and block override earlier ones.
+    for record in records {
+        let history = histories
+            .entry((record.token.as_deref(), record.account.as_str()))
+            .or_default();
+        match record.block {
+            Some(block) => {
+                history.by_block.insert(block, record.balance.clone());
+            }
+            None => history.unblocked = Some(record.balance.clone()),
+        }
+    }
+
+    histories
+}
+
+/// First block at which the balances as of that block differ, both accounts starting from zero.
+fn first_diverging_block(left: Option<&History>, right: Option<&History>) -> Option<u64> {
+    let empty = BTreeMap::new();
+    let left = left.map_or(&empty, |history| &history.by_block);
+    let right = right.map_or(&empty, |history| &history.by_block);
+
+    let blocks: BTreeSet<u64> = left.keys().chain(right.keys()).copied().collect();
+    let (mut left_balance, mut right_balance) = (BigInt::zero(), BigInt::zero());
+
+    for block in blocks {
+        if let Some(balance) = left.get(&block) {
+            left_balance = balance.clone();
+        }
+        if let Some(balance) = right.get(&block) {
+            right_balance = balance.clone();
+        }
+        if left_balance != right_balance {
+            return Some(block);
+        }
+    }
+
+    None
+}
+
+pub fn reconcile(left: &[BalanceRecord], right: &[BalanceRecord]) -> Report {
+    let left = histories(left);
+    let right = histories(right);
+    let keys: BTreeSet<Key> = left.keys().chain(right.keys()).copied().collect();
+    // A dataset without blocks holds no history to compare, only the latest balances.
+    let has_blocks = |histories: &BTreeMap<Key, History>| {
+        histories
+            .values()
+            .any(|history| !history.by_block.is_empty())
+    };
+    let compare_histories = has_blocks(&left) && has_blocks(&right);
+
+    let mut report = Report::default();
+    report.stats.left_accounts = left.len();
+    report.stats.right_accounts = right.len();
+
+    for key in keys {
+        let (left_history, right_history) = (left.get(&key), right.get(&key));
+        match (left_history, right_history) {
+            (Some(_), Some(_)) => report.stats.common_accounts += 1,
+            (Some(_), None) => report.stats.only_left += 1,
+            (None, Some(_)) => report.stats.only_right += 1,
+            (None, None) => unreachable!("account comes from one of the datasets"),
+        }
+
+        let (token, account) = key;
+        let mismatch = AccountMismatch {
+            token: token.map(str::to_string),
+            account: account.to_string(),
+            left: left_history.and_then(History::latest).cloned(),
+            right: right_history.and_then(History::latest).cloned(),
+            first_diverging_block: if compare_histories {
+                first_diverging_block(left_history, right_history)
+            } else {
+                None
+            },
+        };
+        // Histories can diverge and agree again later, the account still mismatches.
+        let difference = mismatch.difference().abs();
+        if difference.is_zero() && mismatch.first_diverging_block.is_none() {
+            report.stats.matching += 1;
+            continue;
+        }
+
+        report.stats.mismatched += 1;
+        report.stats.total_absolute_difference += &difference;
+        if difference > report.stats.max_absolute_difference {
+            report.stats.max_absolute_difference = difference;
+        }
+        report.mismatches.push(mismatch);
+    }
+
+    let order = |mismatch: &AccountMismatch| {
+        (
+            mismatch.first_diverging_block.unwrap_or(u64::MAX),
+            mismatch.token.clone(),
+            mismatch.account.clone(),
+        )
+    };
+    report.mismatches.sort_by_key(order);
+
+    report
+}
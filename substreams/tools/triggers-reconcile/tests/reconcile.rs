@@ -0,0 +1,208 @@
+use num_bigint::BigInt;
+use triggers_reconcile::dataset::{parse_csv, parse_jsonl, select_token, BalanceRecord};
+use triggers_reconcile::reconcile::{reconcile, AccountMismatch};
+
+fn record(account: &str, balance: i64, block: Option<u64>) -> BalanceRecord {
+    BalanceRecord {
+        token: None,
+        account: account.to_string(),
+        balance: BigInt::from(balance),
+        block,
+    }
+}
+
+fn token_record(token: &str, account: &str, balance: i64, block: Option<u64>) -> BalanceRecord {
+    BalanceRecord {
+        token: Some(token.to_string()),
+        ..record(account, balance, block)
+    }
+}
+
+#[test]
+fn parses_subgraph_account_exports() {
+    let csv = "id,grt_balance\n0xAAAA,100\n\"0xbbbb\",\"250\"\n";
+    let jsonl = "{\"id\": \"0xaaaa\", \"grt_balance\": \"100\"}\n\n{\"account\": \"0xbbbb\", \"balance\": 250, \"block\": 12}\n";
+
+    assert_eq!(
+        parse_csv(csv).unwrap(),
+        vec![record("0xaaaa", 100, None), record("0xbbbb", 250, None)]
+    );
+    assert_eq!(
+        parse_jsonl(jsonl).unwrap(),
+        vec![record("0xaaaa", 100, None), record("0xbbbb", 250, Some(12))]
+    );
+}
+
+#[test]
+fn parses_db_out_exports_per_token() {
+    let csv = "id,token,account,balance,evt_block_number\n0xT1:0xaaaa,0xT1,0xAAAA,100,12\n0xt2:0xaaaa,0xt2,0xaaaa,5,13\n";
+
+    assert_eq!(
+        parse_csv(csv).unwrap(),
+        vec![
+            token_record("0xt1", "0xaaaa", 100, Some(12)),
+            token_record("0xt2", "0xaaaa", 5, Some(13)),
+        ]
+    );
+}
+
+#[test]
+fn parses_quoted_fields() {
+    let csv = "account,note,balance\n\"0xaaaa\",\"a, \"\"quoted\"\" note\",100\n";
+
+    assert_eq!(parse_csv(csv).unwrap(), vec![record("0xaaaa", 100, None)]);
+
+    let error = parse_csv("account,balance\n\"0xaaaa,100\n").unwrap_err();
+    assert_eq!(error.to_string(), "line 2: unterminated quoted field");
+}
+
+#[test]
+fn reports_malformed_lines() {
+    let error = parse_csv("account,balance\n0xaaaa,12.5\n").unwrap_err();
+
+    assert!(error.to_string().starts_with("line 2:"));
+}
+
+#[test]
+fn reports_mismatches_with_their_first_diverging_block() {
+    let left = vec![
+        record("0xaaaa", 100, Some(10)),
+        record("0xaaaa", 60, Some(20)),
+        record("0xaaaa", 80, Some(30)),
+        record("0xbbbb", 5, Some(10)),
+        record("0xcccc", 7, Some(15)),
+    ];
+    let right = vec![
+        record("0xaaaa", 100, Some(10)),
+        record("0xaaaa", 70, Some(20)),
+        record("0xaaaa", 90, Some(30)),
+        record("0xbbbb", 5, Some(10)),
+        record("0xdddd", 0, Some(11)),
+    ];
+
+    let report = reconcile(&left, &right);
+
+    assert_eq!(
+        report.mismatches,
+        vec![
+            AccountMismatch {
+                token: None,
+                account: "0xcccc".to_string(),
+                left: Some(BigInt::from(7)),
+                right: None,
+                first_diverging_block: Some(15),
+            },
+            AccountMismatch {
+                token: None,
+                account: "0xaaaa".to_string(),
+                left: Some(BigInt::from(80)),
+                right: Some(BigInt::from(90)),
+                first_diverging_block: Some(20),
+            },
+        ]
+    );
+    assert_eq!(report.mismatches[1].difference(), BigInt::from(-10));
+
+    let stats = &report.stats;
+    assert_eq!(stats.left_accounts, 3);
+    assert_eq!(stats.right_accounts, 3);
+    assert_eq!(stats.common_accounts, 2);
+    assert_eq!(stats.only_left, 1);
+    assert_eq!(stats.only_right, 1);
+    // An account missing from one side matches when the other side holds no balance.
+    assert_eq!(stats.matching, 2);
+    assert_eq!(stats.mismatched, 2);
+    assert_eq!(stats.total_absolute_difference, BigInt::from(17));
+    assert_eq!(stats.max_absolute_difference, BigInt::from(10));
+}
+
+#[test]
+fn compares_snapshots_without_blocks() {
+    let report = reconcile(
+        &[record("0xaaaa", 100, None)],
+        &[record("0xaaaa", 90, None)],
+    );
+
+    assert_eq!(report.mismatches.len(), 1);
+    assert_eq!(report.mismatches[0].first_diverging_block, None);
+}
+
+#[test]
+fn reports_histories_diverging_then_agreeing_again() {
+    let left = vec![
+        record("0xaaaa", 100, Some(10)),
+        record("0xaaaa", 50, Some(20)),
+    ];
+    let right = vec![
+        record("0xaaaa", 90, Some(10)),
+        record("0xaaaa", 50, Some(20)),
+    ];
+
+    let report = reconcile(&left, &right);
+
+    assert_eq!(report.mismatches.len(), 1);
+    assert_eq!(report.mismatches[0].first_diverging_block, Some(10));
+    assert_eq!(report.mismatches[0].difference(), BigInt::from(0));
+    assert_eq!(report.stats.matching, 0);
+    assert_eq!(report.stats.mismatched, 1);
+}
+
+#[test]
+fn compares_tokens_separately() {
+    let left = vec![
+        token_record("0xt1", "0xaaaa", 100, None),
+        token_record("0xt2", "0xaaaa", 5, None),
+    ];
+    let right = vec![
+        token_record("0xt1", "0xaaaa", 100, None),
+        token_record("0xt2", "0xaaaa", 6, None),
+    ];
+
+    let report = reconcile(&left, &right);
+
+    assert_eq!(
+        report.mismatches,
+        vec![AccountMismatch {
+            token: Some("0xt2".to_string()),
+            account: "0xaaaa".to_string(),
+            left: Some(BigInt::from(5)),
+            right: Some(BigInt::from(6)),
+            first_diverging_block: None,
+        }]
+    );
+    assert_eq!(report.stats.common_accounts, 2);
+}
+
+#[test]
+fn compares_a_token_to_a_single_token_dataset() {
+    let left = select_token(
+        vec![
+            token_record("0xt1", "0xaaaa", 100, None),
+            token_record("0xt2", "0xaaaa", 5, None),
+        ],
+        "0xt1",
+    );
+
+    let report = reconcile(&left, &[record("0xaaaa", 100, None)]);
+
+    assert!(report.mismatches.is_empty());
+    assert_eq!(report.stats.common_accounts, 1);
+}
+
+#[test]
+fn compares_a_history_to_a_snapshot_by_latest_balances() {
+    let left = vec![
+        record("0xaaaa", 100, Some(10)),
+        record("0xaaaa", 60, Some(20)),
+    ];
+    let right = vec![record("0xaaaa", 60, None)];
+
+    let report = reconcile(&left, &right);
+
+    assert!(report.mismatches.is_empty());
+    assert_eq!(report.stats.matching, 1);
+
+    let report = reconcile(&left, &[record("0xaaaa", 50, None)]);
+    assert_eq!(report.mismatches.len(), 1);
+    assert_eq!(report.mismatches[0].first_diverging_block, None);
+}
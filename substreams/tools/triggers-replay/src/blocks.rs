@@ -0,0 +1,231 @@
+use prost::Message;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use substreams_ethereum::pb::eth::v2 as eth;
+
+// Merged block files are dbin files: the `dbin` magic, a version byte and the content type,
+// followed by the `sf.bstream.v1.Block` messages, each prefixed with its length as a 4 byte
+// big-endian integer.
+const DBIN_MAGIC: &[u8] = b"dbin";
+// Version 0 headers hold a 3 byte content type and a 2 byte content version, version 1 headers a
+// content type string prefixed with its length as a 2 byte big-endian integer.
+const DBIN_V0_CONTENT_TYPE_LENGTH: usize = 5;
+const ETH_BLOCK_TYPE_URL: &str = "type.googleapis.com/sf.ethereum.type.v2.Block";
+
+/// The envelope of the blocks in merged block files, keeping only the fields needed to get the
+/// chain specific block out of it.
+#[derive(Clone, PartialEq, Message)]
+struct BstreamBlock {
+    #[prost(uint64, tag = "1")]
+    number: u64,
+    /// Chain specific block of the files written before the `payload` field was introduced.
+    #[prost(bytes = "vec", tag = "8")]
+    payload_buffer: Vec<u8>,
+    #[prost(message, optional, tag = "11")]
+    payload: Option<prost_types::Any>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Firehose merged block files (`.dbin`).
+    Merged,
+    /// Blocks prefixed with their varint encoded length, as written by
+    /// `prost::Message::encode_length_delimited`.
+    Delimited,
+    /// A single protobuf encoded block, as the fixtures of the tests.
+    Single,
+}
+
+impl InputFormat {
+    /// Guesses the format of a file from its content, or its extension for a single block.
+    pub fn detect(path: &Path, bytes: &[u8]) -> InputFormat {
+        if bytes.starts_with(DBIN_MAGIC) {
+            return InputFormat::Merged;
+        }
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("binpb") | Some("pb") => InputFormat::Single,
+            _ => InputFormat::Delimited,
+        }
+    }
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "merged" => Ok(InputFormat::Merged),
+            "delimited" => Ok(InputFormat::Delimited),
+            "single" => Ok(InputFormat::Single),
+            unknown => Err(format!(
+                "unknown format '{}', expected merged, delimited or single",
+                unknown
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BlocksError {
+    Io(PathBuf, std::io::Error),
+    /// Merged block files are usually stored compressed with zstd.
+    Compressed(PathBuf),
+    Decode {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl fmt::Display for BlocksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlocksError::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            BlocksError::Compressed(path) => write!(
+                f,
+                "{} is compressed, decompress it first with `zstd -d`",
+                path.display()
+            ),
+            BlocksError::Decode { path, message } => {
+                write!(f, "failed to decode {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BlocksError {}
+
+/// Reads the blocks of a file, or of every file of a directory in file name order, which is the
+/// block order of merged block files. Files are read one at a time as the blocks are consumed, so
+/// that large directories don't have to fit in memory and stopping early skips the rest.
+pub fn read_blocks(
+    path: &Path,
+    format: Option<InputFormat>,
+) -> Result<impl Iterator<Item = Result<eth::Block, BlocksError>>, BlocksError> {
+    let files = input_files(path)?;
+
+    Ok(files.into_iter().flat_map(move |file| {
+        let (blocks, error) = match read_file(&file, format) {
+            Ok(blocks) => (blocks, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        blocks.into_iter().map(Ok).chain(error.map(Err))
+    }))
+}
+
+fn input_files(path: &Path) -> Result<Vec<PathBuf>, BlocksError> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = std::fs::read_dir(path)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| BlocksError::Io(path.to_path_buf(), e))?;
+    files.retain(|file| file.is_file());
+    files.sort();
+    Ok(files)
+}
+
+fn read_file(path: &Path, format: Option<InputFormat>) -> Result<Vec<eth::Block>, BlocksError> {
+    if matches!(path.extension().and_then(|e| e.to_str()), Some("zst")) {
+        return Err(BlocksError::Compressed(path.to_path_buf()));
+    }
+
+    let bytes = std::fs::read(path).map_err(|e| BlocksError::Io(path.to_path_buf(), e))?;
+    let decoded = match format.unwrap_or_else(|| InputFormat::detect(path, &bytes)) {
+        InputFormat::Merged => decode_merged_blocks(&bytes),
+        InputFormat::Delimited => decode_delimited_blocks(&bytes),
+        InputFormat::Single => eth::Block::decode(bytes.as_slice())
+            .map(|blk| vec![blk])
+            .map_err(|e| e.to_string()),
+    };
+
+    decoded.map_err(|message| BlocksError::Decode {
+        path: path.to_path_buf(),
+        message,
+    })
+}
+
+/// Decodes the Ethereum blocks of an uncompressed merged block file.
+pub fn decode_merged_blocks(bytes: &[u8]) -> Result<Vec<eth::Block>, String> {
+    let mut messages = dbin_messages(bytes)?;
+    let mut blocks = Vec::new();
+
+    while !messages.is_empty() {
+        let (length, rest) = split_at(messages, 4, "message length")?;
+        let length = u32::from_be_bytes([length[0], length[1], length[2], length[3]]) as usize;
+        let (message, rest) = split_at(rest, length, "message")?;
+        messages = rest;
+
+        let bstream_block = BstreamBlock::decode(message).map_err(|e| e.to_string())?;
+        let payload = match &bstream_block.payload {
+            Some(payload) if payload.type_url == ETH_BLOCK_TYPE_URL => payload.value.as_slice(),
+            Some(payload) => {
+                return Err(format!(
+                    "block {} holds a {}, expected an Ethereum block",
+                    bstream_block.number, payload.type_url
+                ))
+            }
+            None => bstream_block.payload_buffer.as_slice(),
+        };
+
+        let blk = eth::Block::decode(payload)
+            .map_err(|e| format!("block {}: {}", bstream_block.number, e))?;
+        blocks.push(blk);
+    }
+
+    Ok(blocks)
+}
+
+/// Decodes a dump of varint length prefixed blocks.
+pub fn decode_delimited_blocks(mut bytes: &[u8]) -> Result<Vec<eth::Block>, String> {
+    let mut blocks = Vec::new();
+
+    while !bytes.is_empty() {
+        let blk = eth::Block::decode_length_delimited(&mut bytes)
+            .map_err(|e| format!("block #{}: {}", blocks.len(), e))?;
+        blocks.push(blk);
+    }
+
+    Ok(blocks)
+}
+
+/// Skips the dbin header, returning the length prefixed messages that follow it.
+fn dbin_messages(bytes: &[u8]) -> Result<&[u8], String> {
+    let rest = bytes
+        .strip_prefix(DBIN_MAGIC)
+        .ok_or_else(|| "not a merged block file, the dbin magic is missing".to_string())?;
+    let (version, rest) = split_at(rest, 1, "dbin version")?;
+
+    match version[0] {
+        0 => Ok(split_at(rest, DBIN_V0_CONTENT_TYPE_LENGTH, "dbin content type")?.1),
+        1 => {
+            let (length, rest) = split_at(rest, 2, "dbin content type length")?;
+            let length = u16::from_be_bytes([length[0], length[1]]) as usize;
+            Ok(split_at(rest, length, "dbin content type")?.1)
+        }
+        version => Err(format!("unsupported dbin version {}", version)),
+    }
+}
+
+fn split_at<'a>(
+    bytes: &'a [u8],
+    length: usize,
+    what: &str,
+) -> Result<(&'a [u8], &'a [u8]), String> {
+    if bytes.len() < length {
+        return Err(format!(
+            "truncated {}, expected {} bytes, got {}",
+            what,
+            length,
+            bytes.len()
+        ));
+    }
+
+    Ok(bytes.split_at(length))
+}
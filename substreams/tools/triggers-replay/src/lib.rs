@@ -0,0 +1,5 @@
+//! Runs the substreams modules natively over blocks read from local files, without a Firehose
+//! endpoint.
+
+pub mod blocks;
+pub mod replay;
@@ -0,0 +1,177 @@
+use prost::Message;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use triggers_replay::blocks::{self, InputFormat};
+use triggers_replay::replay::{BlockOutputs, Replay};
+
+const USAGE: &str = "\
+Usage: triggers-replay [OPTIONS] <INPUT>...
+
+Runs map_transfers and graph_out over blocks read from local files, in the order given. Inputs are
+uncompressed Firehose merged block files, dumps of varint length prefixed blocks, single protobuf
+encoded blocks, or directories of them.
+
+Options:
+    --format <merged|delimited|single>  Format of the inputs, guessed from each file by default
+    --params <PARAMS>                   Params of map_transfers, e.g. contracts=0x...
+    --graph-out-params <PARAMS>         Params of graph_out, e.g. token=0x..., mode=strict by default
+    -s, --start-block <N>               First block to output, earlier blocks only feed the stores
+    -t, --stop-block <N>                Block to stop at, exclusive
+    --module <map_transfers|graph_out>  Module to print, both by default
+    -o, --output <DIR>                  Write the protobuf encoded outputs to
+                                        <DIR>/<block>/<module>.binpb instead of printing them
+    -h, --help                          Print this help";
+
+struct Args {
+    inputs: Vec<PathBuf>,
+    format: Option<InputFormat>,
+    params: String,
+    graph_out_params: String,
+    start_block: u64,
+    stop_block: u64,
+    module: Option<String>,
+    output: Option<PathBuf>,
+}
+
+const MODULES: &[&str] = &["map_transfers", "graph_out"];
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut parsed = Args {
+        inputs: Vec::new(),
+        format: None,
+        params: String::new(),
+        graph_out_params: "mode=strict".to_string(),
+        start_block: 0,
+        stop_block: u64::MAX,
+        module: None,
+        output: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", name))
+        };
+        let block = |name: &str, value: String| {
+            value
+                .parse::<u64>()
+                .map_err(|e| format!("invalid {}: {}", name, e))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--format" => parsed.format = Some(value("--format")?.parse()?),
+            "--params" => parsed.params = value("--params")?,
+            "--graph-out-params" => parsed.graph_out_params = value("--graph-out-params")?,
+            "-s" | "--start-block" => parsed.start_block = block(&arg, value(&arg)?)?,
+            "-t" | "--stop-block" => parsed.stop_block = block(&arg, value(&arg)?)?,
+            "--module" => {
+                let module = value("--module")?;
+                if !MODULES.contains(&module.as_str()) {
+                    return Err(format!(
+                        "unknown module '{}', expected one of {}",
+                        module,
+                        MODULES.join(", ")
+                    ));
+                }
+                parsed.module = Some(module);
+            }
+            "-o" | "--output" => parsed.output = Some(PathBuf::from(value(&arg)?)),
+            flag if flag.starts_with('-') => return Err(format!("unknown option {}", flag)),
+            input => parsed.inputs.push(PathBuf::from(input)),
+        }
+    }
+
+    if parsed.inputs.is_empty() {
+        return Err("expected at least one input".to_string());
+    }
+    Ok(Some(parsed))
+}
+
+fn write_outputs(dir: &Path, module: &str, output: &impl Message) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+
+    let path = dir.join(format!("{}.binpb", module));
+    std::fs::write(&path, output.encode_to_vec())
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+fn emit(args: &Args, outputs: &BlockOutputs) -> Result<(), String> {
+    let selected = |module: &str| args.module.as_deref().unwrap_or(module) == module;
+
+    match &args.output {
+        Some(output) => {
+            let dir = output.join(outputs.number.to_string());
+            if selected("map_transfers") {
+                write_outputs(&dir, "map_transfers", &outputs.map_transfers)?;
+            }
+            if selected("graph_out") {
+                write_outputs(&dir, "graph_out", &outputs.graph_out)?;
+            }
+        }
+        None => {
+            if selected("map_transfers") {
+                println!(
+                    "block {} map_transfers: {:#?}",
+                    outputs.number, outputs.map_transfers
+                );
+            }
+            if selected("graph_out") {
+                println!(
+                    "block {} graph_out: {:#?}",
+                    outputs.number, outputs.graph_out
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let mut replay =
+        Replay::new(&args.params, &args.graph_out_params).map_err(|e| e.to_string())?;
+    let mut replayed = 0;
+
+    // Inputs are given in block order, so no block is left to replay past the stop block.
+    'inputs: for input in &args.inputs {
+        for blk in blocks::read_blocks(input, args.format).map_err(|e| e.to_string())? {
+            let blk = blk.map_err(|e| e.to_string())?;
+            if blk.number >= args.stop_block {
+                break 'inputs;
+            }
+
+            let outputs = replay.process(&blk).map_err(|e| e.to_string())?;
+            if blk.number >= args.start_block {
+                emit(args, &outputs)?;
+                replayed += 1;
+            }
+        }
+    }
+
+    eprintln!("replayed {} blocks", replayed);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
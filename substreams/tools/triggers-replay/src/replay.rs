@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use substreams::errors::Error;
+use substreams_entity_change::pb::entity::EntityChanges;
+use substreams_entity_change::tables::Tables as EntityChangesTables;
+use substreams_ethereum::pb::eth::v2 as eth;
+use triggers_poc::params::{SinkParams, TokenParams};
+use triggers_poc::pb::contract::v1 as contract;
+
+/// Outputs of the replayed modules for a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockOutputs {
+    pub number: u64,
+    pub map_transfers: contract::Transfers,
+    pub graph_out: EntityChanges,
+}
+
+/// Runs `map_transfers` and `graph_out` over consecutive blocks. The store `map_transfers` reads
+/// from, `store_balance_slots`, is kept in memory and fed by `map_balance_slots` over the same
+/// blocks, the way the substreams engine schedules them.
+pub struct Replay {
+    params: TokenParams,
+    sink_params: SinkParams,
+    balance_slots: HashMap<String, i64>,
+}
+
+impl Replay {
+    /// `params` are the params of the token modules (`map_transfers` and `map_balance_slots`),
+    /// `sink_params` the ones of `graph_out`.
+    pub fn new(params: &str, sink_params: &str) -> Result<Self, Error> {
+        Ok(Replay {
+            params: TokenParams::parse(params)?,
+            sink_params: SinkParams::parse(sink_params)?,
+            balance_slots: HashMap::new(),
+        })
+    }
+
+    pub fn process(&mut self, blk: &eth::Block) -> Result<BlockOutputs, Error> {
+        self.update_store(blk);
+
+        let map_transfers = contract::Transfers {
+            transfers: triggers_poc::extract_transfers(&self.params, blk, &self.balance_slots)
+                .into_iter()
+                .map(triggers_poc::transfer_to_v1)
+                .collect(),
+        };
+
+        let mut tables = EntityChangesTables::new();
+        triggers_poc::graph_grt_out(&map_transfers, &mut tables, &self.sink_params)
+            .map_err(|e| Error::msg(format!("graph_out failed at block {}: {}", blk.number, e)))?;
+
+        Ok(BlockOutputs {
+            number: blk.number,
+            map_transfers,
+            graph_out: tables.to_entity_changes(),
+        })
+    }
+
+    /// Applies the block to the store before the modules reading it run, as the store deltas of a
+    /// block are visible to the modules of the same block.
+    fn update_store(&mut self, blk: &eth::Block) {
+        // Same update policy as `store_balance_slots`, set_if_not_exists.
+        for balance_slot in triggers_poc::extract_balance_slots(&self.params, blk).balance_slots {
+            self.balance_slots
+                .entry(balance_slot.token)
+                .or_insert(balance_slot.slot as i64);
+        }
+    }
+}
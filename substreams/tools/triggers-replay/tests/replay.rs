@@ -0,0 +1,94 @@
+use prost::Message;
+use substreams_ethereum::pb::eth::v2 as eth;
+use triggers_replay::blocks::{decode_delimited_blocks, decode_merged_blocks};
+use triggers_replay::replay::Replay;
+
+/// `sf.bstream.v1.Block`, with the fields written by the Firehose merger.
+#[derive(Clone, PartialEq, Message)]
+struct BstreamBlock {
+    #[prost(uint64, tag = "1")]
+    number: u64,
+    #[prost(string, tag = "2")]
+    id: String,
+    #[prost(message, optional, tag = "11")]
+    payload: Option<prost_types::Any>,
+}
+
+fn block(number: u64) -> eth::Block {
+    eth::Block {
+        number,
+        hash: vec![number as u8; 32],
+        ..Default::default()
+    }
+}
+
+fn merged_block_file(blocks: &[eth::Block]) -> Vec<u8> {
+    let content_type = b"type.googleapis.com/sf.bstream.v1.Block";
+    let mut file = b"dbin".to_vec();
+    file.push(1);
+    file.extend((content_type.len() as u16).to_be_bytes());
+    file.extend(content_type);
+
+    for blk in blocks {
+        let message = BstreamBlock {
+            number: blk.number,
+            id: String::new(),
+            payload: Some(prost_types::Any {
+                type_url: "type.googleapis.com/sf.ethereum.type.v2.Block".to_string(),
+                value: blk.encode_to_vec(),
+            }),
+        }
+        .encode_to_vec();
+        file.extend((message.len() as u32).to_be_bytes());
+        file.extend(message);
+    }
+
+    file
+}
+
+#[test]
+fn decodes_merged_block_files() {
+    let blocks = vec![block(100), block(101)];
+
+    assert_eq!(
+        decode_merged_blocks(&merged_block_file(&blocks)).unwrap(),
+        blocks
+    );
+}
+
+#[test]
+fn rejects_truncated_merged_block_files() {
+    let file = merged_block_file(&[block(100)]);
+
+    let error = decode_merged_blocks(&file[..file.len() - 1]).unwrap_err();
+
+    assert!(error.starts_with("truncated message"), "{}", error);
+}
+
+#[test]
+fn decodes_length_delimited_dumps() {
+    let blocks = vec![block(100), block(101)];
+    let mut dump = Vec::new();
+    for blk in &blocks {
+        blk.encode_length_delimited(&mut dump).unwrap();
+    }
+
+    assert_eq!(decode_delimited_blocks(&dump).unwrap(), blocks);
+}
+
+#[test]
+fn replays_blocks_without_transfers() {
+    let mut replay = Replay::new("", "mode=strict").unwrap();
+
+    let outputs = replay.process(&block(100)).unwrap();
+
+    assert_eq!(outputs.number, 100);
+    assert!(outputs.map_transfers.transfers.is_empty());
+    assert!(outputs.graph_out.entity_changes.is_empty());
+}
+
+#[test]
+fn rejects_invalid_params() {
+    assert!(Replay::new("contracts=0x12", "").is_err());
+    assert!(Replay::new("", "mode=eventually").is_err());
+}